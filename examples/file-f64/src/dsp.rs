@@ -26,6 +26,11 @@ pub const FAUST_INPUTS: usize = 2;
 pub const FAUST_OUTPUTS: usize = 2;
 pub const FAUST_ACTIVES: usize = 2;
 pub const FAUST_PASSIVES: usize = 2;
+/// The `volume` dsp.
+///
+/// - Author: Franz Heinzmann
+/// - License: BSD
+/// - Version: 1.0
 #[cfg_attr(feature = "default-boxed", derive(default_boxed::DefaultBoxed))]
 #[repr(C)]
 pub struct Amplifer {
@@ -289,6 +294,12 @@ impl FaustDsp for Amplifer {
         self.compute(count as usize, inputs, outputs)
     }
 }
+impl FaustDspConsts for Amplifer {
+    const INPUTS: usize = FAUST_INPUTS;
+    const OUTPUTS: usize = FAUST_OUTPUTS;
+    const ACTIVES: usize = FAUST_ACTIVES;
+    const PASSIVES: usize = FAUST_PASSIVES;
+}
 use strum::{
     Display, EnumIter, EnumCount, EnumDiscriminants, IntoStaticStr, VariantArray,
     VariantNames,
@@ -309,7 +320,13 @@ use strum::{
 )]
 #[strum_discriminants(name(UIActive))]
 pub enum UIActiveValue {
+    /// Vertical slider `/volume/channel_0/volume`.
+    ///
+    /// `-70` to `4`, step `0.1`, initially `0`.
     Channel0Volume(FaustFloat),
+    /// Vertical slider `/volume/channel_1/volume`.
+    ///
+    /// `-70` to `4`, step `0.1`, initially `0`.
     Channel1Volume(FaustFloat),
 }
 impl UISelfSet<Amplifer, FaustFloat> for UIActiveValue {
@@ -341,6 +358,145 @@ impl UIActive {
             UIActive::Channel1Volume => UIActiveValue::Channel1Volume(value),
         }
     }
+    pub const fn param_index(&self) -> TypedParamIndex<Amplifer> {
+        // SAFETY: the indices are taken from the json description of the dsp.
+        unsafe {
+            match self {
+                UIActive::Channel0Volume => TypedParamIndex::new_unchecked(ParamIndex(1i32)),
+                UIActive::Channel1Volume => TypedParamIndex::new_unchecked(ParamIndex(3i32)),
+            }
+        }
+    }
+    pub fn set_normalized(&self, dsp: &mut Amplifer, normalized: FaustFloat) {
+        UISet::set(self, dsp, self.from_normalized(normalized));
+    }
+    pub const fn min(&self) -> FaustFloat {
+        match self {
+            UIActive::Channel0Volume => -70.0,
+            UIActive::Channel1Volume => -70.0,
+        }
+    }
+    pub const fn max(&self) -> FaustFloat {
+        match self {
+            UIActive::Channel0Volume => 4.0,
+            UIActive::Channel1Volume => 4.0,
+        }
+    }
+    pub const fn init(&self) -> FaustFloat {
+        match self {
+            UIActive::Channel0Volume => 0.0,
+            UIActive::Channel1Volume => 0.0,
+        }
+    }
+    pub const fn step(&self) -> FaustFloat {
+        match self {
+            UIActive::Channel0Volume => 0.1,
+            UIActive::Channel1Volume => 0.1,
+        }
+    }
+    pub const fn label(&self) -> &'static str {
+        match self {
+            UIActive::Channel0Volume => "volume",
+            UIActive::Channel1Volume => "volume",
+        }
+    }
+    pub const fn address(&self) -> &'static str {
+        match self {
+            UIActive::Channel0Volume => "/volume/channel_0/volume",
+            UIActive::Channel1Volume => "/volume/channel_1/volume",
+        }
+    }
+    pub const fn kind(&self) -> collect::WidgetKind<FaustFloat> {
+        match self {
+            UIActive::Channel0Volume => {
+                collect::WidgetKind::VerticalSlider {
+                    init: 0.0,
+                    min: -70.0,
+                    max: 4.0,
+                    step: 0.1,
+                }
+            }
+            UIActive::Channel1Volume => {
+                collect::WidgetKind::VerticalSlider {
+                    init: 0.0,
+                    min: -70.0,
+                    max: 4.0,
+                    step: 0.1,
+                }
+            }
+        }
+    }
+    pub const fn metadata(&self) -> &'static [(&'static str, &'static str)] {
+        match self {
+            UIActive::Channel0Volume => &[],
+            UIActive::Channel1Volume => &[],
+        }
+    }
+    pub const fn range(&self) -> ParamRange {
+        match self {
+            UIActive::Channel0Volume => {
+                ParamRange::new(-70f64, 4f64, 0.1f64, Scale::Linear)
+            }
+            UIActive::Channel1Volume => {
+                ParamRange::new(-70f64, 4f64, 0.1f64, Scale::Linear)
+            }
+        }
+    }
+    pub const fn unit(&self) -> &'static str {
+        match self {
+            UIActive::Channel0Volume => "",
+            UIActive::Channel1Volume => "",
+        }
+    }
+    pub fn to_normalized(&self, value: FaustFloat) -> FaustFloat {
+        self.range().to_normalized(value as f64) as FaustFloat
+    }
+    pub fn from_normalized(&self, normalized: FaustFloat) -> FaustFloat {
+        self.range().from_normalized(normalized as f64) as FaustFloat
+    }
+    pub fn get_normalized(&self, dsp: &Amplifer) -> FaustFloat {
+        let value = match self {
+            UIActive::Channel0Volume => dsp.fVslider0,
+            UIActive::Channel1Volume => dsp.fVslider1,
+        };
+        self.to_normalized(value)
+    }
+    pub fn to_text(&self, value: FaustFloat) -> String {
+        self.range().to_text(value as f64, self.unit())
+    }
+    pub fn from_text(&self, text: &str) -> Option<FaustFloat> {
+        self.range().from_text(text, self.unit()).map(|value| value as FaustFloat)
+    }
+}
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Params {
+    pub channel_0_volume: FaustFloat,
+    pub channel_1_volume: FaustFloat,
+}
+impl Default for Params {
+    fn default() -> Self {
+        Self {
+            channel_0_volume: 0.0,
+            channel_1_volume: 0.0,
+        }
+    }
+}
+impl Params {
+    pub fn apply(&self, dsp: &mut Amplifer) {
+        dsp.fVslider0 = self.channel_0_volume;
+        dsp.fVslider1 = self.channel_1_volume;
+    }
+    pub fn read(dsp: &Amplifer) -> Self {
+        Self {
+            channel_0_volume: dsp.fVslider0,
+            channel_1_volume: dsp.fVslider1,
+        }
+    }
+}
+impl Amplifer {
+    pub fn set(&mut self, value: impl UISelfSet<Self, FaustFloat>) {
+        value.set(self);
+    }
 }
 #[derive(
     Debug,
@@ -358,7 +514,13 @@ impl UIActive {
 )]
 #[strum_discriminants(name(UIPassive))]
 pub enum UIPassiveValue {
+    /// Vertical bargraph `/volume/channel_0/level`.
+    ///
+    /// `-60` to `5` dB.
     Channel0Level(FaustFloat),
+    /// Vertical bargraph `/volume/channel_1/level`.
+    ///
+    /// `-60` to `5` dB.
     Channel1Level(FaustFloat),
 }
 impl UIGet<Amplifer> for UIPassive {
@@ -384,7 +546,110 @@ impl UIPassive {
             UIPassive::Channel1Level => UIPassiveValue::Channel1Level(value),
         }
     }
+    pub const fn param_index(&self) -> TypedParamIndex<Amplifer> {
+        // SAFETY: the indices are taken from the json description of the dsp.
+        unsafe {
+            match self {
+                UIPassive::Channel0Level => TypedParamIndex::new_unchecked(ParamIndex(0i32)),
+                UIPassive::Channel1Level => TypedParamIndex::new_unchecked(ParamIndex(2i32)),
+            }
+        }
+    }
+    pub const fn min(&self) -> FaustFloat {
+        match self {
+            UIPassive::Channel0Level => -60.0,
+            UIPassive::Channel1Level => -60.0,
+        }
+    }
+    pub const fn max(&self) -> FaustFloat {
+        match self {
+            UIPassive::Channel0Level => 5.0,
+            UIPassive::Channel1Level => 5.0,
+        }
+    }
+    pub const fn init(&self) -> FaustFloat {
+        match self {
+            UIPassive::Channel0Level => -60.0,
+            UIPassive::Channel1Level => -60.0,
+        }
+    }
+    pub const fn step(&self) -> FaustFloat {
+        match self {
+            UIPassive::Channel0Level => 0.0,
+            UIPassive::Channel1Level => 0.0,
+        }
+    }
+    pub const fn label(&self) -> &'static str {
+        match self {
+            UIPassive::Channel0Level => "level",
+            UIPassive::Channel1Level => "level",
+        }
+    }
+    pub const fn address(&self) -> &'static str {
+        match self {
+            UIPassive::Channel0Level => "/volume/channel_0/level",
+            UIPassive::Channel1Level => "/volume/channel_1/level",
+        }
+    }
+    pub const fn kind(&self) -> collect::WidgetKind<FaustFloat> {
+        match self {
+            UIPassive::Channel0Level => {
+                collect::WidgetKind::VerticalBargraph {
+                    min: -60.0,
+                    max: 5.0,
+                }
+            }
+            UIPassive::Channel1Level => {
+                collect::WidgetKind::VerticalBargraph {
+                    min: -60.0,
+                    max: 5.0,
+                }
+            }
+        }
+    }
+    pub const fn metadata(&self) -> &'static [(&'static str, &'static str)] {
+        match self {
+            UIPassive::Channel0Level => &[("2", ""), ("style", "dB"), ("unit", "dB")],
+            UIPassive::Channel1Level => &[("2", ""), ("style", "dB"), ("unit", "dB")],
+        }
+    }
+    pub const fn range(&self) -> ParamRange {
+        match self {
+            UIPassive::Channel0Level => {
+                ParamRange::new(-60f64, 5f64, 0f64, Scale::Linear)
+            }
+            UIPassive::Channel1Level => {
+                ParamRange::new(-60f64, 5f64, 0f64, Scale::Linear)
+            }
+        }
+    }
+    pub const fn unit(&self) -> &'static str {
+        match self {
+            UIPassive::Channel0Level => "dB",
+            UIPassive::Channel1Level => "dB",
+        }
+    }
+    pub fn to_normalized(&self, value: FaustFloat) -> FaustFloat {
+        self.range().to_normalized(value as f64) as FaustFloat
+    }
+    pub fn from_normalized(&self, normalized: FaustFloat) -> FaustFloat {
+        self.range().from_normalized(normalized as f64) as FaustFloat
+    }
+    pub fn get_normalized(&self, dsp: &Amplifer) -> FaustFloat {
+        let value = match self {
+            UIPassive::Channel0Level => dsp.fVbargraph0,
+            UIPassive::Channel1Level => dsp.fVbargraph1,
+        };
+        self.to_normalized(value)
+    }
+    pub fn to_text(&self, value: FaustFloat) -> String {
+        self.range().to_text(value as f64, self.unit())
+    }
+    pub fn from_text(&self, text: &str) -> Option<FaustFloat> {
+        self.range().from_text(text, self.unit()).map(|value| value as FaustFloat)
+    }
 }
+/// The `volume` group.
 #[derive(Debug)]
 pub struct DspUiVolume {
     pub channel_0: DspUiVolumeChannel0,
@@ -397,7 +662,11 @@ impl DspUiVolume {
             channel_1: DspUiVolumeChannel1::static_ui(),
         }
     }
+    pub const fn label(&self) -> &'static str {
+        "volume"
+    }
 }
+/// The `channel_0` group.
 #[derive(Debug)]
 pub struct DspUiVolumeChannel0 {
     pub level: UIPassive,
@@ -410,7 +679,11 @@ impl DspUiVolumeChannel0 {
             volume: UIActive::Channel0Volume,
         }
     }
+    pub const fn label(&self) -> &'static str {
+        "channel_0"
+    }
 }
+/// The `channel_1` group.
 #[derive(Debug)]
 pub struct DspUiVolumeChannel1 {
     pub level: UIPassive,
@@ -423,5 +696,8 @@ impl DspUiVolumeChannel1 {
             volume: UIActive::Channel1Volume,
         }
     }
+    pub const fn label(&self) -> &'static str {
+        "channel_1"
+    }
 }
 pub static DSP_UI: DspUiVolume = DspUiVolume::static_ui();
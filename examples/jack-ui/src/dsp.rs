@@ -26,6 +26,11 @@ pub const FAUST_INPUTS: usize = 2;
 pub const FAUST_OUTPUTS: usize = 2;
 pub const FAUST_ACTIVES: usize = 1;
 pub const FAUST_PASSIVES: usize = 1;
+/// The `volume` dsp.
+///
+/// - Author: Franz Heinzmann
+/// - License: BSD
+/// - Version: 1.0
 #[cfg_attr(feature = "default-boxed", derive(default_boxed::DefaultBoxed))]
 #[repr(C)]
 pub struct Volume {
@@ -242,6 +247,12 @@ impl FaustDsp for Volume {
         self.compute(count as usize, inputs, outputs)
     }
 }
+impl FaustDspConsts for Volume {
+    const INPUTS: usize = FAUST_INPUTS;
+    const OUTPUTS: usize = FAUST_OUTPUTS;
+    const ACTIVES: usize = FAUST_ACTIVES;
+    const PASSIVES: usize = FAUST_PASSIVES;
+}
 use strum::{
     Display, EnumIter, EnumCount, EnumDiscriminants, IntoStaticStr, VariantArray,
     VariantNames,
@@ -262,6 +273,9 @@ use strum::{
 )]
 #[strum_discriminants(name(UIActive))]
 pub enum UIActiveValue {
+    /// Vertical slider `/volume/volume`.
+    ///
+    /// `-70` to `4`, step `0.1`, initially `0`.
     Volume(FaustFloat),
 }
 impl UISelfSet<Volume, FaustFloat> for UIActiveValue {
@@ -289,6 +303,114 @@ impl UIActive {
             UIActive::Volume => UIActiveValue::Volume(value),
         }
     }
+    pub const fn param_index(&self) -> TypedParamIndex<Volume> {
+        // SAFETY: the indices are taken from the json description of the dsp.
+        unsafe {
+            match self {
+                UIActive::Volume => TypedParamIndex::new_unchecked(ParamIndex(1i32)),
+            }
+        }
+    }
+    pub fn set_normalized(&self, dsp: &mut Volume, normalized: FaustFloat) {
+        UISet::set(self, dsp, self.from_normalized(normalized));
+    }
+    pub const fn min(&self) -> FaustFloat {
+        match self {
+            UIActive::Volume => -70.0,
+        }
+    }
+    pub const fn max(&self) -> FaustFloat {
+        match self {
+            UIActive::Volume => 4.0,
+        }
+    }
+    pub const fn init(&self) -> FaustFloat {
+        match self {
+            UIActive::Volume => 0.0,
+        }
+    }
+    pub const fn step(&self) -> FaustFloat {
+        match self {
+            UIActive::Volume => 0.1,
+        }
+    }
+    pub const fn label(&self) -> &'static str {
+        match self {
+            UIActive::Volume => "volume",
+        }
+    }
+    pub const fn address(&self) -> &'static str {
+        match self {
+            UIActive::Volume => "/volume/volume",
+        }
+    }
+    pub const fn kind(&self) -> collect::WidgetKind<FaustFloat> {
+        match self {
+            UIActive::Volume => {
+                collect::WidgetKind::VerticalSlider {
+                    init: 0.0,
+                    min: -70.0,
+                    max: 4.0,
+                    step: 0.1,
+                }
+            }
+        }
+    }
+    pub const fn metadata(&self) -> &'static [(&'static str, &'static str)] {
+        match self {
+            UIActive::Volume => &[],
+        }
+    }
+    pub const fn range(&self) -> ParamRange {
+        match self {
+            UIActive::Volume => ParamRange::new(-70f64, 4f64, 0.1f64, Scale::Linear),
+        }
+    }
+    pub const fn unit(&self) -> &'static str {
+        match self {
+            UIActive::Volume => "",
+        }
+    }
+    pub fn to_normalized(&self, value: FaustFloat) -> FaustFloat {
+        self.range().to_normalized(value as f64) as FaustFloat
+    }
+    pub fn from_normalized(&self, normalized: FaustFloat) -> FaustFloat {
+        self.range().from_normalized(normalized as f64) as FaustFloat
+    }
+    pub fn get_normalized(&self, dsp: &Volume) -> FaustFloat {
+        let value = match self {
+            UIActive::Volume => dsp.fVslider0,
+        };
+        self.to_normalized(value)
+    }
+    pub fn to_text(&self, value: FaustFloat) -> String {
+        self.range().to_text(value as f64, self.unit())
+    }
+    pub fn from_text(&self, text: &str) -> Option<FaustFloat> {
+        self.range().from_text(text, self.unit()).map(|value| value as FaustFloat)
+    }
+}
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Params {
+    pub volume: FaustFloat,
+}
+impl Default for Params {
+    fn default() -> Self {
+        Self { volume: 0.0 }
+    }
+}
+impl Params {
+    pub fn apply(&self, dsp: &mut Volume) {
+        dsp.fVslider0 = self.volume;
+    }
+    pub fn read(dsp: &Volume) -> Self {
+        Self { volume: dsp.fVslider0 }
+    }
+}
+impl Volume {
+    pub fn set(&mut self, value: impl UISelfSet<Self, FaustFloat>) {
+        value.set(self);
+    }
 }
 #[derive(
     Debug,
@@ -306,6 +428,9 @@ impl UIActive {
 )]
 #[strum_discriminants(name(UIPassive))]
 pub enum UIPassiveValue {
+    /// Vertical bargraph `/volume/level`.
+    ///
+    /// `-60` to `5` dB.
     Level(FaustFloat),
 }
 impl UIGet<Volume> for UIPassive {
@@ -328,7 +453,89 @@ impl UIPassive {
             UIPassive::Level => UIPassiveValue::Level(value),
         }
     }
+    pub const fn param_index(&self) -> TypedParamIndex<Volume> {
+        // SAFETY: the indices are taken from the json description of the dsp.
+        unsafe {
+            match self {
+                UIPassive::Level => TypedParamIndex::new_unchecked(ParamIndex(0i32)),
+            }
+        }
+    }
+    pub const fn min(&self) -> FaustFloat {
+        match self {
+            UIPassive::Level => -60.0,
+        }
+    }
+    pub const fn max(&self) -> FaustFloat {
+        match self {
+            UIPassive::Level => 5.0,
+        }
+    }
+    pub const fn init(&self) -> FaustFloat {
+        match self {
+            UIPassive::Level => -60.0,
+        }
+    }
+    pub const fn step(&self) -> FaustFloat {
+        match self {
+            UIPassive::Level => 0.0,
+        }
+    }
+    pub const fn label(&self) -> &'static str {
+        match self {
+            UIPassive::Level => "level",
+        }
+    }
+    pub const fn address(&self) -> &'static str {
+        match self {
+            UIPassive::Level => "/volume/level",
+        }
+    }
+    pub const fn kind(&self) -> collect::WidgetKind<FaustFloat> {
+        match self {
+            UIPassive::Level => {
+                collect::WidgetKind::VerticalBargraph {
+                    min: -60.0,
+                    max: 5.0,
+                }
+            }
+        }
+    }
+    pub const fn metadata(&self) -> &'static [(&'static str, &'static str)] {
+        match self {
+            UIPassive::Level => &[("2", ""), ("style", "dB"), ("unit", "dB")],
+        }
+    }
+    pub const fn range(&self) -> ParamRange {
+        match self {
+            UIPassive::Level => ParamRange::new(-60f64, 5f64, 0f64, Scale::Linear),
+        }
+    }
+    pub const fn unit(&self) -> &'static str {
+        match self {
+            UIPassive::Level => "dB",
+        }
+    }
+    pub fn to_normalized(&self, value: FaustFloat) -> FaustFloat {
+        self.range().to_normalized(value as f64) as FaustFloat
+    }
+    pub fn from_normalized(&self, normalized: FaustFloat) -> FaustFloat {
+        self.range().from_normalized(normalized as f64) as FaustFloat
+    }
+    pub fn get_normalized(&self, dsp: &Volume) -> FaustFloat {
+        let value = match self {
+            UIPassive::Level => dsp.fVbargraph0,
+        };
+        self.to_normalized(value)
+    }
+    pub fn to_text(&self, value: FaustFloat) -> String {
+        self.range().to_text(value as f64, self.unit())
+    }
+    pub fn from_text(&self, text: &str) -> Option<FaustFloat> {
+        self.range().from_text(text, self.unit()).map(|value| value as FaustFloat)
+    }
 }
+/// The `volume` group.
 #[derive(Debug)]
 pub struct DspUiVolume {
     pub level: UIPassive,
@@ -341,5 +548,8 @@ impl DspUiVolume {
             volume: UIActive::Volume,
         }
     }
+    pub const fn label(&self) -> &'static str {
+        "volume"
+    }
 }
 pub static DSP_UI: DspUiVolume = DspUiVolume::static_ui();
@@ -2,6 +2,10 @@ use dsp::{UIActive, UIActiveValue, UIPassive, UIPassiveValue, Volume, DSP_UI};
 use faust_types::{FaustDsp, UIGet, UISelfSet};
 use std::{
     io,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
     thread::{self, sleep},
     time::Duration,
 };
@@ -13,7 +17,7 @@ fn main() {
     let mut dsp = Volume::new();
     // Get number of inputs and ouputs
     let num_inputs = dsp.get_num_inputs() as usize;
-    let num_outputs = dsp.get_num_inputs() as usize;
+    let num_outputs = dsp.get_num_outputs() as usize;
 
     eprintln!("inputs: {num_inputs}");
     eprintln!("outputs: {num_outputs}");
@@ -55,10 +59,13 @@ fn main() {
     let sample_rate = client.sample_rate();
     dsp.init(sample_rate as i32);
 
+    // Blocks output as silence because they could not be processed, counted instead of
+    // logged as the process callback must not block or allocate.
+    let failed_blocks = Arc::new(AtomicUsize::new(0));
+    let failed = Arc::clone(&failed_blocks);
+
     // Create JACK process closure that runs for each buffer
     let process_callback = move |_: &jack::Client, ps: &jack::ProcessScope| -> jack::Control {
-        let len = ps.n_frames() as usize;
-
         let inputs = in_ports.iter().map(|p| p.as_slice(ps)).collect::<Vec<_>>();
         let mut outputs = out_ports
             .iter_mut()
@@ -73,7 +80,13 @@ fn main() {
         send_passive.publish();
 
         // run dsp computation
-        dsp.compute(len, &inputs, &mut outputs);
+        if dsp.process(&inputs, &mut outputs).is_err() {
+            failed.fetch_add(1, Ordering::Relaxed);
+            for output in &mut outputs {
+                output.fill(0.0);
+            }
+            return jack::Control::Continue;
+        }
 
         // send all passive controls
         let buffer = send_passive.input_buffer_mut();
@@ -95,4 +108,12 @@ fn main() {
     let mut user_input = String::new();
     io::stdin().read_line(&mut user_input).ok();
     active_client.deactivate().unwrap();
+
+    let failed_blocks = failed_blocks.load(Ordering::Relaxed);
+    if failed_blocks > 0 {
+        eprintln!(
+            "{} blocks did not match the channels of the dsp",
+            failed_blocks
+        );
+    }
 }
@@ -1,16 +1,22 @@
 use faust_state::DspHandle;
-use faust_types::FaustDsp;
+use faust_types::{buffer::ChannelBuffers, dynamic::DynFaustDsp};
 use jack::AudioIn;
 use jack::*;
-use std::{io, slice};
+use std::{
+    io,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+};
 
 pub fn run_dsp_as_jack_client<T>(mut dsp: DspHandle<T>)
 where
-    T: FaustDsp<T = f32> + 'static + Send,
+    T: DynFaustDsp<f32> + ?Sized + 'static + Send,
 {
     // Get number of inputs and ouputs
     let num_inputs = dsp.num_inputs();
-    let num_outputs = dsp.num_inputs();
+    let num_outputs = dsp.num_outputs();
 
     // Create JACK client
     let (client, in_ports, mut out_ports) = create_jack_client(dsp.name(), num_inputs, num_outputs);
@@ -21,40 +27,44 @@ where
 
     // Init input and output buffers
     let buffer_size = client.buffer_size() as usize;
-    let mut inputs: Vec<Vec<f32>> = vec![vec![0_f32; buffer_size]; num_inputs];
-    let mut outputs: Vec<Vec<f32>> = vec![vec![0_f32; buffer_size]; num_outputs];
+    let mut buffers = ChannelBuffers::new(num_inputs, num_outputs, buffer_size);
 
-    // Map our Vec<Vec<f32>> to a Vec<&f[32]> to create a buffer for the faust lib
-    let buffer_input: Vec<&[f32]> = inputs
-        .iter()
-        .map(|input| unsafe { slice::from_raw_parts(input.as_ptr(), buffer_size) })
-        .collect();
-
-    // Map our Vec<Vec<f32>> to a Vec<&f[32]> to create a buffer for the faust lib
-    let mut buffer_output: Vec<&mut [f32]> = outputs
-        .iter_mut()
-        .map(|output| unsafe { slice::from_raw_parts_mut(output.as_mut_ptr(), buffer_size) })
-        .collect();
+    // Blocks output as silence because they could not be processed, counted instead of
+    // logged as the process callback must not block or allocate.
+    let failed_blocks = Arc::new(AtomicUsize::new(0));
+    let failed = Arc::clone(&failed_blocks);
 
     // Create JACK process closure that runs for each buffer
     let process_callback = move |_: &jack::Client, ps: &jack::ProcessScope| -> jack::Control {
-        let len = ps.n_frames();
-        assert!(len as usize <= buffer_size);
+        let len = ps.n_frames() as usize;
+        if len > buffer_size {
+            failed.fetch_add(1, Ordering::Relaxed);
+            silence(&mut out_ports, ps);
+            return jack::Control::Continue;
+        }
 
         // Copy audio input for all ports from jack to the faust input buffer
-        for index_port in 0..num_inputs {
-            let port = in_ports[index_port].as_slice(ps);
-            inputs[index_port][0..len as usize].copy_from_slice(port);
+        for (index_port, port) in in_ports.iter().enumerate() {
+            buffers.input_mut(index_port)[..len].copy_from_slice(port.as_slice(ps));
         }
 
-        // Call the update_and_compute handler on the Faust DSP. This first processes param changes
+        // Call the update_and_process handler on the Faust DSP. This first processes param changes
         // from the State handler and then computes the outputs from the inputs and params.
-        dsp.update_and_compute(len as i32, &buffer_input[..], &mut buffer_output[..]);
+        if buffers
+            .process_with(len, |inputs, outputs| {
+                dsp.update_and_process(inputs, outputs)
+            })
+            .is_err()
+        {
+            failed.fetch_add(1, Ordering::Relaxed);
+            silence(&mut out_ports, ps);
+            return jack::Control::Continue;
+        }
 
         // Copy audio output for all ports from faust to the jack output
-        for index_port in 0..num_outputs {
-            let port = out_ports[index_port].as_mut_slice(ps);
-            port.copy_from_slice(&outputs[index_port][0..len as usize]);
+        for (index_port, port) in out_ports.iter_mut().enumerate() {
+            port.as_mut_slice(ps)
+                .copy_from_slice(&buffers.output(index_port)[..len]);
         }
 
         jack::Control::Continue
@@ -70,6 +80,20 @@ where
     let mut user_input = String::new();
     io::stdin().read_line(&mut user_input).ok();
     active_client.deactivate().unwrap();
+
+    let failed_blocks = failed_blocks.load(Ordering::Relaxed);
+    if failed_blocks > 0 {
+        eprintln!(
+            "{} blocks were longer than {} frames or did not match the channels of the dsp",
+            failed_blocks, buffer_size
+        );
+    }
+}
+
+fn silence(out_ports: &mut [Port<AudioOut>], ps: &jack::ProcessScope) {
+    for port in out_ports {
+        port.as_mut_slice(ps).fill(0.0);
+    }
 }
 
 pub fn create_jack_client(
@@ -242,3 +242,9 @@ impl FaustDsp for Volumecontrol {
         self.compute(count as usize, inputs, outputs)
     }
 }
+impl FaustDspConsts for Volumecontrol {
+    const INPUTS: usize = FAUST_INPUTS;
+    const OUTPUTS: usize = FAUST_OUTPUTS;
+    const ACTIVES: usize = FAUST_ACTIVES;
+    const PASSIVES: usize = FAUST_PASSIVES;
+}
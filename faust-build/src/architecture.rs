@@ -1,7 +1,14 @@
-use crate::builder::FaustBuilder;
-use proc_macro2::TokenStream;
+use crate::{
+    builder::FaustBuilder,
+    code_option::{CodeOption, CodeOptionDiscriminants},
+    template,
+};
+use proc_macro2::{Delimiter, Group, Ident, TokenStream, TokenTree};
 use quote::quote;
-use std::path::{Path, PathBuf};
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+};
 use syn::parse_str;
 
 pub enum Architecture {
@@ -24,6 +31,20 @@ impl Architecture {
         Self::Function(&mod_ui)
     }
 
+    /// Exports a constructor so the dsp can be loaded from a dynamic library,
+    /// and `faust_dsp_abi_version` to check that the loader was built compatibly.
+    #[cfg(feature = "hot-reload")]
+    #[must_use]
+    pub fn dylib() -> Self {
+        Self::Function(&dylib)
+    }
+
+    /// Runs the stages of the pipeline in order.
+    #[must_use]
+    pub fn pipeline(pipeline: Pipeline) -> Self {
+        Self::Object(Box::new(pipeline))
+    }
+
     #[must_use]
     pub fn file(path: PathBuf) -> Self {
         Self::File(path)
@@ -37,6 +58,10 @@ impl Architecture {
         }
     }
 
+    /// Applies the architecture.
+    ///
+    /// The built-in architectures and architecture files implement `FaustDspConsts` with
+    /// the [`AssociatedConsts`] stage, pipelines implement it if they include the stage.
     #[allow(clippy::option_if_let_else)]
     pub(crate) fn apply(&self, builder: &FaustBuilder, dsp_code: &str) -> TokenStream {
         match self {
@@ -57,89 +82,178 @@ impl Architecture {
                 architecture_interface.apply(builder, &ts)
             }
             Self::File(_path_buf) => {
-                let dsp_code = if let Some(mn) = builder.get_module_name() {
-                    &dsp_code.replace("<<moduleName>>", mn)
-                } else {
-                    dsp_code
-                };
-                parse_str::<TokenStream>(dsp_code).expect("Failed to parse string into tokens")
+                let values = template_values(builder, dsp_code);
+                let dsp_code = template::render(dsp_code, &values);
+                let ts = parse_str::<TokenStream>(&dsp_code)
+                    .expect("Failed to parse string into tokens");
+                // `<<includeclass>>` is replaced by the class including its `FaustDsp` impl.
+                AssociatedConsts.apply(builder, &ts)
             }
         }
     }
 }
 
+/// Values for the placeholders of an architecture file.
+///
+/// - `<<moduleName>>`, `<<structName>>`: identifiers as set in the builder
+/// - `<<inputs>>`, `<<outputs>>`: number of channels
+/// - `<<dspName>>`: the declared name as string literal
+/// - `<<metadata>>`: `&[(key, value), ..]` with all declared metadata
+/// - `<<jsonDescription>>`: the json file as string literal
+/// - `<<uiEnums>>`: the code generated by faust-ui
+/// - all variables set with `FaustBuilder::set_template_var`
+///
+/// The last four need the `faust-ui` feature and the json file.
+fn template_values(builder: &FaustBuilder, dsp_code: &str) -> HashMap<String, String> {
+    let mut values = builder.get_template_vars().clone();
+    if let Some(module_name) = builder.get_module_name() {
+        values.insert("moduleName".into(), module_name.clone());
+    }
+    if let Some(CodeOption::StructName(struct_name)) =
+        builder.get_code_option(&CodeOptionDiscriminants::StructName)
+    {
+        values.insert("structName".into(), struct_name.clone());
+    }
+    for (key, constant) in [("inputs", "FAUST_INPUTS"), ("outputs", "FAUST_OUTPUTS")] {
+        if let Some(n) = const_value(dsp_code, constant) {
+            values.insert(key.into(), n.into());
+        }
+    }
+    #[cfg(feature = "faust-ui")]
+    json_template_values(builder, dsp_code, &mut values);
+    values
+}
+
+/// Finds the value of a `pub const NAME: usize = value;` declaration generated by faust.
+fn const_value<'a>(dsp_code: &'a str, name: &str) -> Option<&'a str> {
+    let start = dsp_code.find(&format!("{name}: usize ="))?;
+    let rest = &dsp_code[start..];
+    let value = &rest[rest.find('=')? + 1..rest.find(';')?];
+    Some(value.trim())
+}
+
+#[cfg(feature = "faust-ui")]
+fn json_template_values(
+    builder: &FaustBuilder,
+    dsp_code: &str,
+    values: &mut HashMap<String, String>,
+) {
+    let json_path = builder.get_json_path();
+    let Ok(json) = std::fs::read_to_string(&json_path) else {
+        return;
+    };
+    let faust_json: faust_json::FaustJson =
+        serde_json::from_str(&json).unwrap_or_else(|err| panic!("json parsing error: {}", err));
+    values.insert("dspName".into(), format!("{:?}", faust_json.name));
+    let metadata: Vec<TokenStream> = faust_json
+        .meta
+        .iter()
+        .map(|faust_json::Meta { key, value }| quote! { (#key, #value) })
+        .collect();
+    values.insert("metadata".into(), quote! { &[#(#metadata),*] }.to_string());
+    values.insert("jsonDescription".into(), format!("{json:?}"));
+    if dsp_code.contains("uiEnums>>") {
+        let ui_code = ui_code(builder, &json_path);
+        values.insert("uiEnums".into(), ui_code.to_string());
+    }
+}
+
+/// The faust-ui code with the options of the builder,
+/// accessing the parameters by index if the dsp is a wrapper of the C backend.
+#[cfg(feature = "faust-ui")]
+fn ui_code(builder: &FaustBuilder, json_path: &Path) -> TokenStream {
+    #[cfg(feature = "c-backend")]
+    let access = if builder.get_backend() == crate::c_backend::Backend::Rust {
+        faust_ui::ParamAccess::Field
+    } else {
+        faust_ui::ParamAccess::Index
+    };
+    #[cfg(not(feature = "c-backend"))]
+    let access = faust_ui::ParamAccess::Field;
+    let options = faust_ui::UiOptions {
+        access,
+        ..builder.get_ui_options()
+    };
+    FaustBuilder::generate_ui_from_json_with_options(json_path, builder.get_struct_name(), options)
+}
+
 impl Default for Architecture {
     fn default() -> Self {
         Self::Function(&default)
     }
 }
+/// A transformation of the generated code.
+///
+/// Implementations can be combined into a [`Pipeline`].
 pub trait ObjectInterface {
     fn apply(&self, builder: &FaustBuilder, dsp_code: &TokenStream) -> TokenStream;
 }
 
-#[must_use]
-pub fn default(_builder: &FaustBuilder, dsp_code: &TokenStream) -> TokenStream {
-    quote! {
-        #![allow(clippy::all)]
-        #![allow(unused_parens)]
-        #![allow(non_snake_case)]
-        #![allow(non_camel_case_types)]
-        #![allow(dead_code)]
-        #![allow(unused_variables)]
-        #![allow(unused_mut)]
-        #![allow(non_upper_case_globals)]
-        use faust_types::*;
-        #dsp_code
+impl<F> ObjectInterface for F
+where
+    F: Fn(&FaustBuilder, &TokenStream) -> TokenStream,
+{
+    fn apply(&self, builder: &FaustBuilder, dsp_code: &TokenStream) -> TokenStream {
+        self(builder, dsp_code)
     }
 }
 
-#[cfg(feature = "faust-ui")]
-#[must_use]
-fn ui(builder: &FaustBuilder, dsp_code: &TokenStream) -> TokenStream {
-    let struct_name = builder.get_struct_name();
-    let json_path = builder.get_json_path();
-    match std::fs::exists(&json_path) {
-        Ok(b) => {
-            assert!(b, "json file not found at path: {:?}", json_path);
-        }
-        Err(err) => core::panic!("json file not found at path: {:?}", err),
+/// Chain of stages, each stage transforms the output of the previous one.
+///
+/// ```
+/// # use faust_build::{architecture::*, builder::FaustBuilder};
+/// # use proc_macro2::TokenStream;
+/// # fn my_stage(_builder: &FaustBuilder, code: &TokenStream) -> TokenStream {
+/// #     code.clone()
+/// # }
+/// # fn run(builder: &mut FaustBuilder) {
+/// let pipeline = Pipeline::new()
+///     .with(AllowLints)
+///     .with(AssociatedConsts)
+///     .with(UiEnums)
+///     .with(my_stage)
+///     .with(WrapInModule::default());
+/// builder.set_architecture(Architecture::pipeline(pipeline));
+/// # }
+/// ```
+#[derive(Default)]
+pub struct Pipeline {
+    stages: Vec<Box<dyn ObjectInterface>>,
+}
+
+impl Pipeline {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
     }
-    let ui_code = FaustBuilder::generate_ui_from_json(&json_path, struct_name);
-    quote! {
-        #![allow(clippy::all)]
-        #![allow(unused_parens)]
-        #![allow(non_snake_case)]
-        #![allow(non_camel_case_types)]
-        #![allow(dead_code)]
-        #![allow(unused_variables)]
-        #![allow(unused_mut)]
-        #![allow(non_upper_case_globals)]
-        use faust_types::*;
-        #dsp_code
-        #ui_code
+
+    #[must_use]
+    pub fn with(mut self, stage: impl ObjectInterface + 'static) -> Self {
+        self.push(stage);
+        self
+    }
+
+    pub fn push(&mut self, stage: impl ObjectInterface + 'static) {
+        self.stages.push(Box::new(stage));
     }
 }
 
-#[cfg(feature = "faust-ui")]
-#[must_use]
-fn mod_ui(builder: &FaustBuilder, dsp_code: &TokenStream) -> TokenStream {
-    let module_name = builder
-        .get_module_name()
-        .as_ref()
-        .expect("module name needed y mod ui architecture function");
-    let struct_name = builder.get_struct_name();
-    let json_path = builder.get_json_path();
-    match std::fs::exists(&json_path) {
-        Ok(b) => {
-            assert!(b, "json file not found at path: {:?}", json_path);
-        }
-        Err(err) => core::panic!("json file not found at path: {:?}", err),
+impl ObjectInterface for Pipeline {
+    fn apply(&self, builder: &FaustBuilder, dsp_code: &TokenStream) -> TokenStream {
+        self.stages
+            .iter()
+            .fold(dsp_code.clone(), |code, stage| stage.apply(builder, &code))
     }
-    let ui_code = FaustBuilder::generate_ui_from_json(&json_path, struct_name);
+}
 
-    let module_name = quote::format_ident!("{}", module_name);
-    quote! {
-        pub mod #module_name {
+/// Adds the lint exceptions needed by the generated code and imports `faust_types`.
+///
+/// Has to be the first stage, as it adds inner attributes.
+pub struct AllowLints;
+
+impl ObjectInterface for AllowLints {
+    fn apply(&self, _builder: &FaustBuilder, dsp_code: &TokenStream) -> TokenStream {
+        quote! {
             #![allow(clippy::all)]
             #![allow(unused_parens)]
             #![allow(non_snake_case)]
@@ -150,40 +264,345 @@ fn mod_ui(builder: &FaustBuilder, dsp_code: &TokenStream) -> TokenStream {
             #![allow(non_upper_case_globals)]
             use faust_types::*;
             #dsp_code
+        }
+    }
+}
+
+/// Wraps the code in `pub mod name { .. }`.
+///
+/// Without a name the module name of the builder is used.
+#[derive(Default)]
+pub struct WrapInModule {
+    pub name: Option<String>,
+}
+
+impl WrapInModule {
+    #[must_use]
+    pub fn named(name: impl Into<String>) -> Self {
+        Self {
+            name: Some(name.into()),
+        }
+    }
+}
+
+impl ObjectInterface for WrapInModule {
+    fn apply(&self, builder: &FaustBuilder, dsp_code: &TokenStream) -> TokenStream {
+        let module_name = self
+            .name
+            .as_ref()
+            .or_else(|| builder.get_module_name().as_ref())
+            .expect("module name needed to wrap the code in a module");
+        let module_name = quote::format_ident!("{}", module_name);
+        quote! {
+            pub mod #module_name {
+                #dsp_code
+            }
+        }
+    }
+}
+
+/// Adds the `UIActive` and `UIPassive` enums and the `DSP_UI` struct generated from the json file.
+#[cfg(feature = "faust-ui")]
+pub struct UiEnums;
+
+#[cfg(feature = "faust-ui")]
+impl ObjectInterface for UiEnums {
+    fn apply(&self, builder: &FaustBuilder, dsp_code: &TokenStream) -> TokenStream {
+        let json_path = builder.get_json_path();
+        match std::fs::exists(&json_path) {
+            Ok(b) => {
+                assert!(b, "json file not found at path: {}", json_path.display());
+            }
+            Err(err) => core::panic!("json file not found at path: {:?}", err),
+        }
+        let ui_code = ui_code(builder, &json_path);
+        quote! {
+            #dsp_code
             #ui_code
         }
     }
 }
 
-// Architecture Object needs a usecase first
-// pub struct ArchitectureUI {}
-
-// impl ArchitectureInterface for ArchitectureUI {
-//     fn wrap(&self, builder: &FaustBuilder, dsp_code: TokenStream) -> TokenStream {
-//         let struct_name = builder.get_struct_name();
-//         let json_path = builder.get_json_path();
-//         match fs::exists(&json_path) {
-//             Ok(b) => {
-//                 assert!(b, "json file not found at path: {:?}", json_path);
-//             }
-//             Err(err) => panic!("json file not found at path: {:?}", err),
-//         }
-//         let ui_code =
-//             FaustBuilder::get_ui_from_json(&json_path, struct_name);
-//         let struct_name = format_ident!("{}", struct_name);
-//         quote! {
-//                 #![allow(clippy::all)]
-//                 #![allow(unused_parens)]
-//                 #![allow(non_snake_case)]
-//                 #![allow(non_camel_case_types)]
-//                 #![allow(dead_code)]
-//                 #![allow(unused_variables)]
-//                 #![allow(unused_mut)]
-//                 #![allow(non_upper_case_globals)]
-//                 use faust_types::*;
-//                 #dsp_code
-//                 #ui_code
-//             }
-//         }
-//     }
-// }
+/// Adds doc comments with the name, description, author, license and version declared in the dsp
+/// to the dsp struct, read from the json file.
+#[cfg(feature = "faust-ui")]
+pub struct DspDoc;
+
+#[cfg(feature = "faust-ui")]
+impl ObjectInterface for DspDoc {
+    fn apply(&self, builder: &FaustBuilder, dsp_code: &TokenStream) -> TokenStream {
+        let json_path = builder.get_json_path();
+        let json = std::fs::read_to_string(&json_path)
+            .unwrap_or_else(|err| panic!("json file not found at path: {:?}", err));
+        let faust_json: faust_json::FaustJson =
+            serde_json::from_str(&json).unwrap_or_else(|err| panic!("json parsing error: {}", err));
+        let doc = faust_ui::generate_dsp_doc(&faust_json);
+        insert_struct_doc(dsp_code.clone(), builder.get_struct_name(), &doc)
+    }
+}
+
+/// Inserts `doc` before the outer attributes of `pub struct name`.
+#[cfg(feature = "faust-ui")]
+fn insert_struct_doc(dsp_code: TokenStream, name: &str, doc: &TokenStream) -> TokenStream {
+    let mut tokens: Vec<TokenTree> = Vec::new();
+    for token in dsp_code {
+        match token {
+            TokenTree::Group(group) if group.delimiter() == Delimiter::Brace => {
+                let mut new_group = Group::new(
+                    Delimiter::Brace,
+                    insert_struct_doc(group.stream(), name, doc),
+                );
+                new_group.set_span(group.span());
+                tokens.push(new_group.into());
+            }
+            TokenTree::Ident(ident) if ident == name => {
+                let len = tokens.len();
+                let is_struct = matches!(
+                    &tokens[len.saturating_sub(2)..],
+                    [TokenTree::Ident(visibility), TokenTree::Ident(keyword)]
+                        if visibility == "pub" && keyword == "struct"
+                );
+                if is_struct {
+                    let mut start = len - 2;
+                    while let [.., TokenTree::Punct(pound), TokenTree::Group(attribute)] =
+                        &tokens[..start]
+                    {
+                        if pound.as_char() != '#' || attribute.delimiter() != Delimiter::Bracket {
+                            break;
+                        }
+                        start -= 2;
+                    }
+                    tokens.splice(start..start, doc.clone());
+                }
+                tokens.push(ident.into());
+            }
+            token => tokens.push(token),
+        }
+    }
+    tokens.into_iter().collect()
+}
+
+/// Adds the `FaustDsp` implementation delegating to the inherent methods of the dsp struct,
+/// unless faust already generated one.
+pub struct FaustDspImpl;
+
+impl ObjectInterface for FaustDspImpl {
+    fn apply(&self, builder: &FaustBuilder, dsp_code: &TokenStream) -> TokenStream {
+        if contains_impl(dsp_code.clone(), "FaustDsp") {
+            return dsp_code.clone();
+        }
+        let struct_name = quote::format_ident!("{}", builder.get_struct_name());
+        quote! {
+            #dsp_code
+
+            impl FaustDsp for #struct_name {
+                type T = FaustFloat;
+                fn new() -> Self where Self: Sized {
+                    Self::new()
+                }
+                fn metadata(&self, m: &mut dyn Meta) {
+                    self.metadata(m)
+                }
+                fn get_sample_rate(&self) -> i32 {
+                    self.get_sample_rate()
+                }
+                fn get_num_inputs(&self) -> i32 {
+                    FAUST_INPUTS as i32
+                }
+                fn get_num_outputs(&self) -> i32 {
+                    FAUST_OUTPUTS as i32
+                }
+                fn class_init(sample_rate: i32) where Self: Sized {
+                    Self::class_init(sample_rate);
+                }
+                fn instance_reset_params(&mut self) {
+                    self.instance_reset_params()
+                }
+                fn instance_clear(&mut self) {
+                    self.instance_clear()
+                }
+                fn instance_constants(&mut self, sample_rate: i32) {
+                    self.instance_constants(sample_rate)
+                }
+                fn instance_init(&mut self, sample_rate: i32) {
+                    self.instance_init(sample_rate)
+                }
+                fn init(&mut self, sample_rate: i32) {
+                    self.init(sample_rate)
+                }
+                fn build_user_interface(&self, ui_interface: &mut dyn UI<Self::T>) {
+                    self.build_user_interface(ui_interface)
+                }
+                fn build_user_interface_static(ui_interface: &mut dyn UI<Self::T>) where Self: Sized {
+                    Self::build_user_interface_static(ui_interface);
+                }
+                fn get_param(&self, param: ParamIndex) -> Option<Self::T> {
+                    self.get_param(param)
+                }
+                fn set_param(&mut self, param: ParamIndex, value: Self::T) {
+                    self.set_param(param, value)
+                }
+                fn compute(&mut self, count: i32, inputs: &[&[Self::T]], outputs: &mut [&mut [Self::T]]) {
+                    self.compute(count as usize, inputs, outputs)
+                }
+            }
+        }
+    }
+}
+
+/// Implements `FaustDspConsts` with the constants `INPUTS`, `OUTPUTS`, `ACTIVES` and `PASSIVES`
+/// next to every `impl FaustDsp for ..` block, set from the `FAUST_*` constants generated by faust.
+///
+/// Implementations are found in modules too and may name the trait with its path
+/// or have generics. Code already implementing `FaustDspConsts` is left unchanged.
+pub struct AssociatedConsts;
+
+impl ObjectInterface for AssociatedConsts {
+    fn apply(&self, _builder: &FaustBuilder, dsp_code: &TokenStream) -> TokenStream {
+        if contains_impl(dsp_code.clone(), "FaustDspConsts") {
+            return dsp_code.clone();
+        }
+        insert_associated_consts(dsp_code.clone())
+    }
+}
+
+fn insert_associated_consts(dsp_code: TokenStream) -> TokenStream {
+    let tokens: Vec<TokenTree> = dsp_code.into_iter().collect();
+    let mut out: Vec<TokenTree> = Vec::with_capacity(tokens.len());
+    let mut header: Option<(usize, usize)> = None;
+    for (i, token) in tokens.iter().enumerate() {
+        match token {
+            TokenTree::Group(group) if group.delimiter() == Delimiter::Brace => {
+                let mut new_group =
+                    Group::new(Delimiter::Brace, insert_associated_consts(group.stream()));
+                new_group.set_span(group.span());
+                out.push(new_group.into());
+                if let Some((start, trait_name)) = header.take() {
+                    out.extend(consts_impl(&tokens[start..i], trait_name - start));
+                }
+            }
+            token => {
+                if header.is_none() {
+                    header =
+                        impl_of(&tokens, i, "FaustDsp").map(|for_position| (i, for_position - 1));
+                }
+                out.push(token.clone());
+            }
+        }
+    }
+    out.into_iter().collect()
+}
+
+/// `impl FaustDspConsts for ..` with the generics and self type of the `FaustDsp` impl `header`,
+/// the tokens from `impl` to the body, with the trait name at `trait_name`.
+fn consts_impl(header: &[TokenTree], trait_name: usize) -> TokenStream {
+    let header = header.iter().enumerate().map(|(i, token)| {
+        if i == trait_name {
+            Ident::new("FaustDspConsts", token.span()).into()
+        } else {
+            token.clone()
+        }
+    });
+    quote! {
+        #(#header)* {
+            const INPUTS: usize = FAUST_INPUTS;
+            const OUTPUTS: usize = FAUST_OUTPUTS;
+            const ACTIVES: usize = FAUST_ACTIVES;
+            const PASSIVES: usize = FAUST_PASSIVES;
+        }
+    }
+}
+
+/// The position of the `for` of `impl<..> path::name for`, if `tokens[start]` starts
+/// an implementation of the trait `name`.
+fn impl_of(tokens: &[TokenTree], start: usize, name: &str) -> Option<usize> {
+    if !matches!(&tokens[start], TokenTree::Ident(ident) if ident == "impl") {
+        return None;
+    }
+    let mut depth = 0_usize;
+    let mut trait_name = None;
+    for (i, token) in tokens.iter().enumerate().skip(start + 1) {
+        match token {
+            TokenTree::Punct(punct) if punct.as_char() == '<' => depth += 1,
+            TokenTree::Punct(punct) if punct.as_char() == '>' => {
+                // The `>` of `->` in a bound like `F: Fn() -> T` closes nothing.
+                let arrow = matches!(&tokens[i - 1], TokenTree::Punct(p) if p.as_char() == '-');
+                if !arrow {
+                    depth = depth.saturating_sub(1);
+                }
+            }
+            TokenTree::Ident(ident) if depth == 0 && ident == "for" => {
+                return (trait_name.as_deref() == Some(name)).then_some(i);
+            }
+            TokenTree::Ident(ident) if depth == 0 => trait_name = Some(ident.to_string()),
+            TokenTree::Group(group) if group.delimiter() == Delimiter::Brace => return None,
+            TokenTree::Punct(punct) if punct.as_char() == ';' => return None,
+            _ => {}
+        }
+    }
+    None
+}
+
+/// Whether the code implements the trait `name` anywhere, also in modules.
+fn contains_impl(dsp_code: TokenStream, name: &str) -> bool {
+    let tokens: Vec<TokenTree> = dsp_code.into_iter().collect();
+    (0..tokens.len()).any(|i| impl_of(&tokens, i, name).is_some())
+        || tokens.iter().any(|token| match token {
+            TokenTree::Group(group) => contains_impl(group.stream(), name),
+            _ => false,
+        })
+}
+
+#[must_use]
+pub fn default(builder: &FaustBuilder, dsp_code: &TokenStream) -> TokenStream {
+    Pipeline::new()
+        .with(AllowLints)
+        .with(AssociatedConsts)
+        .apply(builder, dsp_code)
+}
+
+#[cfg(feature = "hot-reload")]
+#[must_use]
+fn dylib(builder: &FaustBuilder, dsp_code: &TokenStream) -> TokenStream {
+    let struct_name = quote::format_ident!("{}", builder.get_struct_name());
+    let dsp_code = default(builder, dsp_code);
+    quote! {
+        #dsp_code
+
+        #[no_mangle]
+        pub extern "C" fn faust_dsp_abi_version() -> *const std::os::raw::c_char {
+            faust_types::dynamic::ABI_VERSION_NUL.as_ptr().cast()
+        }
+
+        /// Returns a `Box<Box<dyn DynFaustDsp<FaustFloat> + Send>>` as thin pointer.
+        #[no_mangle]
+        pub extern "C" fn faust_create_dsp() -> *mut std::ffi::c_void {
+            let dsp: Box<dyn faust_types::dynamic::DynFaustDsp<FaustFloat> + Send> =
+                Box::new(#struct_name::new());
+            Box::into_raw(Box::new(dsp)).cast()
+        }
+    }
+}
+
+#[cfg(feature = "faust-ui")]
+#[must_use]
+fn ui(builder: &FaustBuilder, dsp_code: &TokenStream) -> TokenStream {
+    Pipeline::new()
+        .with(AllowLints)
+        .with(AssociatedConsts)
+        .with(DspDoc)
+        .with(UiEnums)
+        .apply(builder, dsp_code)
+}
+
+#[cfg(feature = "faust-ui")]
+#[must_use]
+fn mod_ui(builder: &FaustBuilder, dsp_code: &TokenStream) -> TokenStream {
+    Pipeline::new()
+        .with(AllowLints)
+        .with(AssociatedConsts)
+        .with(DspDoc)
+        .with(UiEnums)
+        .with(WrapInModule::default())
+        .apply(builder, dsp_code)
+}
@@ -12,6 +12,7 @@ use crate::{
 use heck::{CamelCase, SnakeCase};
 use proc_macro2::TokenStream;
 use std::{
+    collections::HashMap,
     env,
     fs::{self},
     io::{BufWriter, Write},
@@ -28,6 +29,13 @@ pub struct FaustBuilder {
     module_name: Option<String>,
     out_path: Option<PathBuf>,
     compile_options: CompileOptions,
+    template_vars: HashMap<String, String>,
+    #[cfg(feature = "faust-ui")]
+    ui_options: faust_ui::UiOptions,
+    #[cfg(feature = "libfaust")]
+    in_process: bool,
+    #[cfg(feature = "c-backend")]
+    backend: crate::c_backend::Backend,
 }
 
 impl Default for FaustBuilder {
@@ -38,6 +46,13 @@ impl Default for FaustBuilder {
             module_name: None,
             out_path: None,
             compile_options: CompileOptions::default(),
+            template_vars: HashMap::new(),
+            #[cfg(feature = "faust-ui")]
+            ui_options: faust_ui::UiOptions::default(),
+            #[cfg(feature = "libfaust")]
+            in_process: false,
+            #[cfg(feature = "c-backend")]
+            backend: crate::c_backend::Backend::default(),
         }
     }
 }
@@ -56,6 +71,41 @@ impl FaustBuilder {
         self.faust_path = faust_path.into();
     }
 
+    /// Selects the output language of faust, `rust` by default.
+    pub fn set_lang(&mut self, lang: impl Into<String>) {
+        self.compile_options.lang = lang.into();
+    }
+
+    /// Compile with the linked libfaust instead of the faust binary.
+    #[cfg(feature = "libfaust")]
+    pub fn set_in_process(&mut self, in_process: bool) {
+        self.in_process = in_process;
+    }
+
+    /// Generates the dsp with the C or C++ backend wrapped in a Rust struct implementing `FaustDsp`.
+    /// See [`c_backend`](crate::c_backend).
+    ///
+    /// Also writes the json file, the wrapper counts the widgets in it.
+    ///
+    /// # Panics
+    /// Panics if an architecture file is set and the backend is not Rust,
+    /// faust would apply it to the C code.
+    #[cfg(feature = "c-backend")]
+    pub fn set_backend(&mut self, backend: crate::c_backend::Backend) {
+        crate::c_backend::assert_architecture(self, backend);
+        self.set_lang(backend.lang());
+        self.backend = backend;
+        if backend != crate::c_backend::Backend::Rust {
+            self.write_json_file();
+        }
+    }
+
+    #[cfg(feature = "c-backend")]
+    #[must_use]
+    pub fn get_backend(&self) -> crate::c_backend::Backend {
+        self.backend
+    }
+
     pub fn set_out_path(&mut self, out_path: impl Into<PathBuf>) {
         self.out_path = Some(out_path.into());
     }
@@ -68,6 +118,29 @@ impl FaustBuilder {
         self.compile_options.architecture = arch;
     }
 
+    #[must_use]
+    pub(crate) const fn get_architecture(&self) -> &Architecture {
+        &self.compile_options.architecture
+    }
+
+    /// Defines a `<<key>>` placeholder for architecture files.
+    pub fn set_template_var(&mut self, key: impl Into<String>, value: impl Into<String>) {
+        self.template_vars.insert(key.into(), value.into());
+    }
+
+    /// Selects what the faust-ui code of the architectures contains.
+    /// The parameter access is chosen by the backend.
+    #[cfg(feature = "faust-ui")]
+    pub fn set_ui_options(&mut self, options: faust_ui::UiOptions) {
+        self.ui_options = options;
+    }
+
+    #[cfg(feature = "faust-ui")]
+    #[must_use]
+    pub fn get_ui_options(&self) -> faust_ui::UiOptions {
+        self.ui_options
+    }
+
     pub fn set_dsp_path(&mut self, dsp_path: impl Into<PathBuf>) {
         self.compile_options.dsp_path = Some(DspPath::File(dsp_path.into()));
     }
@@ -133,6 +206,20 @@ impl FaustBuilder {
 
     #[must_use]
     pub fn run_faust(&self) -> String {
+        #[cfg(feature = "libfaust")]
+        if self.in_process {
+            let output = self
+                .run_libfaust()
+                .unwrap_or_else(|err| panic!("faust compilation failed: \n{}", err));
+            assert!(
+                output.warnings.is_empty(),
+                "Fail on warning from libfaust: \n{}",
+                crate::libfaust::LibFaustError {
+                    diagnostics: output.warnings
+                }
+            );
+            return output.code;
+        }
         let faust_result = Command::new(&self.faust_path)
             .args(self.compile_options.to_command_args())
             .args(CodeOptionToCommandArgs::to_command_args(
@@ -157,6 +244,33 @@ impl FaustBuilder {
         String::from_utf8(faust_result.stdout).expect("could not parse stdout from command")
     }
 
+    /// Compiles the dsp in-process and returns the generated code, json description and warnings.
+    ///
+    /// The json description is written to [`get_json_path`](Self::get_json_path) as well,
+    /// where the architectures read it.
+    ///
+    /// # Errors
+    /// Returns the diagnostics reported by libfaust.
+    #[cfg(feature = "libfaust")]
+    pub fn run_libfaust(
+        &self,
+    ) -> Result<crate::libfaust::LibFaustOutput, crate::libfaust::LibFaustError> {
+        let mut args = self.compile_options.to_option_args();
+        args.extend(CodeOptionToCommandArgs::to_command_args(
+            &self.code_gen_options,
+        ));
+        let output = crate::libfaust::compile(self.get_dsp_path(), &args)?;
+        if let Some(json) = &output.json {
+            fs::write(self.get_json_path(), json).map_err(|err| {
+                crate::libfaust::LibFaustError::error(format!(
+                    "failed to write {}: {err}",
+                    self.get_json_path().display()
+                ))
+            })?;
+        }
+        Ok(output)
+    }
+
     fn pretty(ts: TokenStream) -> String {
         let st = syn::parse2(ts).expect("Failed to parse Rust code in wrapper");
         prettyplease::unparse(&st)
@@ -164,8 +278,25 @@ impl FaustBuilder {
 
     #[allow(clippy::must_use_candidate)]
     pub fn build(&self) -> TokenStream {
-        let dsp_code = self.run_faust();
-        let ts = self.compile_options.architecture.apply(self, &dsp_code);
+        self.build_code(&self.run_faust())
+    }
+
+    /// Compiles the dsp in-process like [`FaustBuilder::build`], but returns the diagnostics
+    /// of a failed compilation instead of panicking. Warnings don't fail the build,
+    /// [`run_libfaust`](Self::run_libfaust) returns them.
+    ///
+    /// # Errors
+    /// Returns the diagnostics reported by libfaust.
+    #[cfg(feature = "libfaust")]
+    pub fn try_build(&self) -> Result<TokenStream, crate::libfaust::LibFaustError> {
+        let output = self.run_libfaust()?;
+        Ok(self.build_code(&output.code))
+    }
+
+    fn build_code(&self, dsp_code: &str) -> TokenStream {
+        #[cfg(feature = "c-backend")]
+        let dsp_code = &crate::c_backend::wrap(self, self.backend, dsp_code);
+        let ts = self.compile_options.architecture.apply(self, dsp_code);
         let dsp_code = Self::pretty(ts.clone());
         if let Some(out_path) = &self.out_path {
             fs::write(out_path, &dsp_code).expect("failed to write to destination path");
@@ -222,6 +353,35 @@ impl FaustBuilder {
     pub fn generate_ui_from_json(
         json_path: &Path,
         struct_name: impl AsRef<str>,
+    ) -> proc_macro2::TokenStream {
+        Self::generate_ui_from_json_with_access(
+            json_path,
+            struct_name,
+            faust_ui::ParamAccess::Field,
+        )
+    }
+
+    #[cfg(feature = "faust-ui")]
+    pub fn generate_ui_from_json_with_access(
+        json_path: &Path,
+        struct_name: impl AsRef<str>,
+        access: faust_ui::ParamAccess,
+    ) -> proc_macro2::TokenStream {
+        Self::generate_ui_from_json_with_options(
+            json_path,
+            struct_name,
+            faust_ui::UiOptions {
+                access,
+                ..faust_ui::UiOptions::default()
+            },
+        )
+    }
+
+    #[cfg(feature = "faust-ui")]
+    pub fn generate_ui_from_json_with_options(
+        json_path: &Path,
+        struct_name: impl AsRef<str>,
+        options: faust_ui::UiOptions,
     ) -> proc_macro2::TokenStream {
         let json_file = std::fs::File::open(json_path).expect("Failed to open json file");
         let json_reader = std::io::BufReader::new(json_file);
@@ -229,7 +389,7 @@ impl FaustBuilder {
             .unwrap_or_else(|err| {
                 panic!("json parsing error: {}", err);
             });
-        faust_ui::generate_ui_code(&faust_json, struct_name)
+        faust_ui::generate_ui_code_with_options(&faust_json, struct_name, options)
     }
 
     #[must_use]
@@ -249,6 +409,29 @@ impl FaustBuilder {
         &self.module_name
     }
 
+    #[must_use]
+    pub fn get_faust_path(&self) -> &Path {
+        &self.faust_path
+    }
+
+    /// Directory containing the faust headers, see `faust -includedir`.
+    #[must_use]
+    pub fn get_include_dir(&self) -> PathBuf {
+        let output = Command::new(&self.faust_path)
+            .arg("-includedir")
+            .output()
+            .expect("Failed to execute faust");
+        str::from_utf8(&output.stdout)
+            .expect("could not parse stdout from command")
+            .trim()
+            .into()
+    }
+
+    #[must_use]
+    pub fn get_template_vars(&self) -> &HashMap<String, String> {
+        &self.template_vars
+    }
+
     #[must_use]
     pub fn get_json_path(&self) -> PathBuf {
         let dsp_path = self.get_dsp_path();
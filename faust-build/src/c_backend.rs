@@ -0,0 +1,668 @@
+//! Build dsps with the C or C++ backend of faust.
+//!
+//! The generated code is compiled with `cc` and wrapped in a Rust struct implementing `FaustDsp`,
+//! so it can be used like a dsp generated by the Rust backend:
+//! ```no_run
+//! # use faust_build::{builder::FaustBuilder, c_backend::Backend};
+//! # use std::{env, path::PathBuf};
+//! // build.rs
+//! # let out_dir = PathBuf::from(env::var_os("OUT_DIR").expect("OUT_DIR is set by cargo"));
+//! let mut b = FaustBuilder::default_for_file_with_ui("dsp/volume.dsp", out_dir.join("volume.rs"));
+//! b.set_backend(Backend::C);
+//! b.build();
+//! ```
+//! The wrapper uses `FaustFloat = f32`, `-double` is rejected. The `UIActive` and `UIPassive` enums of faust-ui access
+//! the parameters through `FaustDsp::set_param` and `FaustDsp::get_param`.
+//! Architecture files are not supported, as faust would apply them to the C code.
+//! Neither are soundfiles, the wrapper has no way to load them.
+//!
+//! Can only be used in build scripts, the C code is written to `OUT_DIR`.
+
+use crate::{
+    architecture::Architecture, builder::FaustBuilder, code_option::CodeOptionDiscriminants,
+    template,
+};
+use faust_json::{FaustJson, LayoutItem};
+use std::{collections::HashMap, env, fs, path::PathBuf};
+
+/// The faust backend generating the dsp code.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Backend {
+    #[default]
+    Rust,
+    C,
+    Cpp,
+}
+
+impl Backend {
+    #[must_use]
+    pub fn lang(self) -> &'static str {
+        match self {
+            Self::Rust => "rust",
+            Self::C => "c",
+            Self::Cpp => "cpp",
+        }
+    }
+}
+
+/// Includes the C code with the definition of `UIGlue` and `MetaGlue` it relies on.
+const C_SHIM: &str = r#"#include "faust/gui/CInterface.h"
+#include "<<structName>>.c"
+"#;
+
+/// Exposes the C++ class with the same functions as the C backend.
+const CPP_SHIM: &str = r#"#include <memory>
+#include "faust/gui/CInterface.h"
+#include "faust/gui/meta.h"
+#include "faust/gui/UI.h"
+#include "faust/dsp/dsp.h"
+#include "<<structName>>.cpp"
+
+namespace {
+
+struct GlueMeta : public Meta {
+    MetaGlue* fGlue;
+    explicit GlueMeta(MetaGlue* glue) : fGlue(glue) {}
+    void declare(const char* key, const char* value) override { fGlue->declare(fGlue->metaInterface, key, value); }
+};
+
+struct GlueUI : public UI {
+    UIGlue* fGlue;
+    explicit GlueUI(UIGlue* glue) : fGlue(glue) {}
+    void openTabBox(const char* label) override { fGlue->openTabBox(fGlue->uiInterface, label); }
+    void openHorizontalBox(const char* label) override { fGlue->openHorizontalBox(fGlue->uiInterface, label); }
+    void openVerticalBox(const char* label) override { fGlue->openVerticalBox(fGlue->uiInterface, label); }
+    void closeBox() override { fGlue->closeBox(fGlue->uiInterface); }
+    void addButton(const char* label, FAUSTFLOAT* zone) override { fGlue->addButton(fGlue->uiInterface, label, zone); }
+    void addCheckButton(const char* label, FAUSTFLOAT* zone) override { fGlue->addCheckButton(fGlue->uiInterface, label, zone); }
+    void addVerticalSlider(const char* label, FAUSTFLOAT* zone, FAUSTFLOAT init, FAUSTFLOAT min, FAUSTFLOAT max, FAUSTFLOAT step) override {
+        fGlue->addVerticalSlider(fGlue->uiInterface, label, zone, init, min, max, step);
+    }
+    void addHorizontalSlider(const char* label, FAUSTFLOAT* zone, FAUSTFLOAT init, FAUSTFLOAT min, FAUSTFLOAT max, FAUSTFLOAT step) override {
+        fGlue->addHorizontalSlider(fGlue->uiInterface, label, zone, init, min, max, step);
+    }
+    void addNumEntry(const char* label, FAUSTFLOAT* zone, FAUSTFLOAT init, FAUSTFLOAT min, FAUSTFLOAT max, FAUSTFLOAT step) override {
+        fGlue->addNumEntry(fGlue->uiInterface, label, zone, init, min, max, step);
+    }
+    void addHorizontalBargraph(const char* label, FAUSTFLOAT* zone, FAUSTFLOAT min, FAUSTFLOAT max) override {
+        fGlue->addHorizontalBargraph(fGlue->uiInterface, label, zone, min, max);
+    }
+    void addVerticalBargraph(const char* label, FAUSTFLOAT* zone, FAUSTFLOAT min, FAUSTFLOAT max) override {
+        fGlue->addVerticalBargraph(fGlue->uiInterface, label, zone, min, max);
+    }
+    void addSoundfile(const char* label, const char* filename, Soundfile** sf_zone) override {
+        fGlue->addSoundfile(fGlue->uiInterface, label, filename, sf_zone);
+    }
+    void declare(FAUSTFLOAT* zone, const char* key, const char* value) override { fGlue->declare(fGlue->uiInterface, zone, key, value); }
+};
+
+}
+
+extern "C" {
+
+<<structName>>* new<<structName>>() { return new <<structName>>(); }
+void delete<<structName>>(<<structName>>* dsp) { delete dsp; }
+void metadata<<structName>>(MetaGlue* m) {
+    GlueMeta meta(m);
+    std::unique_ptr<<<structName>>> dsp(new <<structName>>());
+    dsp->metadata(&meta);
+}
+int getSampleRate<<structName>>(<<structName>>* dsp) { return dsp->getSampleRate(); }
+void classInit<<structName>>(int sample_rate) { <<structName>>::classInit(sample_rate); }
+void instanceResetUserInterface<<structName>>(<<structName>>* dsp) { dsp->instanceResetUserInterface(); }
+void instanceClear<<structName>>(<<structName>>* dsp) { dsp->instanceClear(); }
+void instanceConstants<<structName>>(<<structName>>* dsp, int sample_rate) { dsp->instanceConstants(sample_rate); }
+void instanceInit<<structName>>(<<structName>>* dsp, int sample_rate) { dsp->instanceInit(sample_rate); }
+void init<<structName>>(<<structName>>* dsp, int sample_rate) { dsp->init(sample_rate); }
+void buildUserInterface<<structName>>(<<structName>>* dsp, UIGlue* glue) {
+    GlueUI ui(glue);
+    dsp->buildUserInterface(&ui);
+}
+void compute<<structName>>(<<structName>>* dsp, int count, FAUSTFLOAT** inputs, FAUSTFLOAT** outputs) {
+    dsp->compute(count, inputs, outputs);
+}
+
+}
+"#;
+
+/// The safe Rust wrapper around the functions of the C backend.
+const RUST_WRAPPER: &str = r#"
+pub type FaustFloat = f32;
+pub const FAUST_INPUTS: usize = <<inputs>>;
+pub const FAUST_OUTPUTS: usize = <<outputs>>;
+pub const FAUST_ACTIVES: usize = <<actives>>;
+pub const FAUST_PASSIVES: usize = <<passives>>;
+
+#[allow(non_snake_case, dead_code)]
+mod ffi {
+    use faust_types::{ParamIndex, UI, Meta};
+    use std::ffi::CStr;
+    use std::os::raw::{c_char, c_int, c_void};
+
+    type BoxFn = unsafe extern "C" fn(*mut c_void, *const c_char);
+    type ButtonFn = unsafe extern "C" fn(*mut c_void, *const c_char, *mut f32);
+    type SliderFn = unsafe extern "C" fn(*mut c_void, *const c_char, *mut f32, f32, f32, f32, f32);
+    type BargraphFn = unsafe extern "C" fn(*mut c_void, *const c_char, *mut f32, f32, f32);
+
+    #[repr(C)]
+    pub struct UIGlue {
+        ui_interface: *mut c_void,
+        open_tab_box: BoxFn,
+        open_horizontal_box: BoxFn,
+        open_vertical_box: BoxFn,
+        close_box: unsafe extern "C" fn(*mut c_void),
+        add_button: ButtonFn,
+        add_check_button: ButtonFn,
+        add_vertical_slider: SliderFn,
+        add_horizontal_slider: SliderFn,
+        add_num_entry: SliderFn,
+        add_horizontal_bargraph: BargraphFn,
+        add_vertical_bargraph: BargraphFn,
+        add_soundfile: unsafe extern "C" fn(*mut c_void, *const c_char, *const c_char, *mut c_void),
+        declare: unsafe extern "C" fn(*mut c_void, *mut f32, *const c_char, *const c_char),
+    }
+
+    #[repr(C)]
+    pub struct MetaGlue {
+        meta_interface: *mut c_void,
+        declare: unsafe extern "C" fn(*mut c_void, *const c_char, *const c_char),
+    }
+
+    extern "C" {
+        pub fn new<<structName>>() -> *mut c_void;
+        pub fn delete<<structName>>(dsp: *mut c_void);
+        pub fn metadata<<structName>>(m: *mut MetaGlue);
+        pub fn getSampleRate<<structName>>(dsp: *mut c_void) -> c_int;
+        pub fn classInit<<structName>>(sample_rate: c_int);
+        pub fn instanceResetUserInterface<<structName>>(dsp: *mut c_void);
+        pub fn instanceClear<<structName>>(dsp: *mut c_void);
+        pub fn instanceConstants<<structName>>(dsp: *mut c_void, sample_rate: c_int);
+        pub fn instanceInit<<structName>>(dsp: *mut c_void, sample_rate: c_int);
+        pub fn init<<structName>>(dsp: *mut c_void, sample_rate: c_int);
+        pub fn buildUserInterface<<structName>>(dsp: *mut c_void, ui: *mut UIGlue);
+        pub fn compute<<structName>>(
+            dsp: *mut c_void,
+            count: c_int,
+            inputs: *mut *mut f32,
+            outputs: *mut *mut f32,
+        );
+    }
+
+    /// Receives the callbacks of `buildUserInterface`.
+    /// Without `ui` the zones are collected, otherwise the widgets are forwarded
+    /// with the position of their zone as `ParamIndex`.
+    pub struct Glue<'a> {
+        pub zones: Vec<*mut f32>,
+        pub ui: Option<&'a mut dyn UI<f32>>,
+    }
+
+    impl Glue<'_> {
+        fn widget(&mut self, zone: *mut f32, add: impl FnOnce(&mut dyn UI<f32>, ParamIndex)) {
+            match &mut self.ui {
+                None => self.zones.push(zone),
+                Some(ui) => {
+                    if let Some(index) = index(&self.zones, zone) {
+                        add(&mut **ui, index);
+                    }
+                }
+            }
+        }
+    }
+
+    fn index(zones: &[*mut f32], zone: *mut f32) -> Option<ParamIndex> {
+        let index = zones.iter().position(|z| *z == zone)?;
+        Some(ParamIndex(index as i32))
+    }
+
+    unsafe fn glue<'a, 'b>(ui: *mut c_void) -> &'a mut Glue<'b> {
+        &mut *ui.cast::<Glue<'b>>()
+    }
+
+    unsafe fn text<'a>(s: *const c_char) -> &'a str {
+        CStr::from_ptr(s).to_str().unwrap_or_default()
+    }
+
+    unsafe extern "C" fn open_tab_box(ui: *mut c_void, label: *const c_char) {
+        if let Some(ui) = &mut glue(ui).ui {
+            ui.open_tab_box(text(label));
+        }
+    }
+    unsafe extern "C" fn open_horizontal_box(ui: *mut c_void, label: *const c_char) {
+        if let Some(ui) = &mut glue(ui).ui {
+            ui.open_horizontal_box(text(label));
+        }
+    }
+    unsafe extern "C" fn open_vertical_box(ui: *mut c_void, label: *const c_char) {
+        if let Some(ui) = &mut glue(ui).ui {
+            ui.open_vertical_box(text(label));
+        }
+    }
+    unsafe extern "C" fn close_box(ui: *mut c_void) {
+        if let Some(ui) = &mut glue(ui).ui {
+            ui.close_box();
+        }
+    }
+    unsafe extern "C" fn add_button(ui: *mut c_void, label: *const c_char, zone: *mut f32) {
+        glue(ui).widget(zone, |ui, i| ui.add_button(text(label), i));
+    }
+    unsafe extern "C" fn add_check_button(ui: *mut c_void, label: *const c_char, zone: *mut f32) {
+        glue(ui).widget(zone, |ui, i| ui.add_check_button(text(label), i));
+    }
+    unsafe extern "C" fn add_vertical_slider(
+        ui: *mut c_void,
+        label: *const c_char,
+        zone: *mut f32,
+        init: f32,
+        min: f32,
+        max: f32,
+        step: f32,
+    ) {
+        glue(ui).widget(zone, |ui, i| {
+            ui.add_vertical_slider(text(label), i, init, min, max, step)
+        });
+    }
+    unsafe extern "C" fn add_horizontal_slider(
+        ui: *mut c_void,
+        label: *const c_char,
+        zone: *mut f32,
+        init: f32,
+        min: f32,
+        max: f32,
+        step: f32,
+    ) {
+        glue(ui).widget(zone, |ui, i| {
+            ui.add_horizontal_slider(text(label), i, init, min, max, step)
+        });
+    }
+    unsafe extern "C" fn add_num_entry(
+        ui: *mut c_void,
+        label: *const c_char,
+        zone: *mut f32,
+        init: f32,
+        min: f32,
+        max: f32,
+        step: f32,
+    ) {
+        glue(ui).widget(zone, |ui, i| {
+            ui.add_num_entry(text(label), i, init, min, max, step)
+        });
+    }
+    unsafe extern "C" fn add_horizontal_bargraph(
+        ui: *mut c_void,
+        label: *const c_char,
+        zone: *mut f32,
+        min: f32,
+        max: f32,
+    ) {
+        glue(ui).widget(zone, |ui, i| {
+            ui.add_horizontal_bargraph(text(label), i, min, max)
+        });
+    }
+    unsafe extern "C" fn add_vertical_bargraph(
+        ui: *mut c_void,
+        label: *const c_char,
+        zone: *mut f32,
+        min: f32,
+        max: f32,
+    ) {
+        glue(ui).widget(zone, |ui, i| {
+            ui.add_vertical_bargraph(text(label), i, min, max)
+        });
+    }
+    unsafe extern "C" fn add_soundfile(
+        ui: *mut c_void,
+        _label: *const c_char,
+        _url: *const c_char,
+        _zone: *mut c_void,
+    ) {
+        // Never called, `wrap` rejects dsps with soundfiles.
+        // The null zone keeps the indices in sync with the json.
+        glue(ui).widget(std::ptr::null_mut(), |_, _| {});
+    }
+    unsafe extern "C" fn declare(
+        ui: *mut c_void,
+        zone: *mut f32,
+        key: *const c_char,
+        value: *const c_char,
+    ) {
+        let glue = glue(ui);
+        if let Some(ui) = &mut glue.ui {
+            let param = if zone.is_null() {
+                None
+            } else {
+                match index(&glue.zones, zone) {
+                    Some(index) => Some(index),
+                    None => return,
+                }
+            };
+            ui.declare(param, text(key), text(value));
+        }
+    }
+
+    pub fn ui_glue(glue: &mut Glue) -> UIGlue {
+        UIGlue {
+            ui_interface: (glue as *mut Glue).cast(),
+            open_tab_box,
+            open_horizontal_box,
+            open_vertical_box,
+            close_box,
+            add_button,
+            add_check_button,
+            add_vertical_slider,
+            add_horizontal_slider,
+            add_num_entry,
+            add_horizontal_bargraph,
+            add_vertical_bargraph,
+            add_soundfile,
+            declare,
+        }
+    }
+
+    unsafe extern "C" fn meta_declare(m: *mut c_void, key: *const c_char, value: *const c_char) {
+        let m = &mut *m.cast::<&mut dyn Meta>();
+        m.declare(text(key), text(value));
+    }
+
+    pub fn meta_glue(m: &mut &mut dyn Meta) -> MetaGlue {
+        MetaGlue {
+            meta_interface: (m as *mut &mut dyn Meta).cast(),
+            declare: meta_declare,
+        }
+    }
+}
+
+/// Dsp compiled with the <<lang>> backend of faust.
+pub struct <<structName>> {
+    dsp: *mut std::os::raw::c_void,
+    /// Zones of all widgets, the position is the `ParamIndex`.
+    zones: Vec<*mut FaustFloat>,
+    inputs: [*mut FaustFloat; FAUST_INPUTS],
+    outputs: [*mut FaustFloat; FAUST_OUTPUTS],
+}
+
+// The C struct is owned by the wrapper and only accessed through it.
+unsafe impl Send for <<structName>> {}
+
+impl Drop for <<structName>> {
+    fn drop(&mut self) {
+        unsafe { ffi::delete<<structName>>(self.dsp) }
+    }
+}
+
+impl FaustDspConsts for <<structName>> {
+    const INPUTS: usize = FAUST_INPUTS;
+    const OUTPUTS: usize = FAUST_OUTPUTS;
+    const ACTIVES: usize = FAUST_ACTIVES;
+    const PASSIVES: usize = FAUST_PASSIVES;
+}
+
+impl FaustDsp for <<structName>> {
+    type T = FaustFloat;
+
+    fn new() -> Self {
+        let dsp = unsafe { ffi::new<<structName>>() };
+        assert!(!dsp.is_null(), "failed to allocate dsp");
+        let mut glue = ffi::Glue { zones: Vec::new(), ui: None };
+        unsafe { ffi::buildUserInterface<<structName>>(dsp, &mut ffi::ui_glue(&mut glue)) };
+        Self {
+            dsp,
+            zones: glue.zones,
+            inputs: [std::ptr::null_mut(); FAUST_INPUTS],
+            outputs: [std::ptr::null_mut(); FAUST_OUTPUTS],
+        }
+    }
+    fn metadata(&self, m: &mut dyn Meta) {
+        let mut m = m;
+        unsafe { ffi::metadata<<structName>>(&mut ffi::meta_glue(&mut m)) }
+    }
+    fn get_sample_rate(&self) -> i32 {
+        unsafe { ffi::getSampleRate<<structName>>(self.dsp) }
+    }
+    fn get_num_inputs(&self) -> i32 {
+        FAUST_INPUTS as i32
+    }
+    fn get_num_outputs(&self) -> i32 {
+        FAUST_OUTPUTS as i32
+    }
+    fn class_init(sample_rate: i32) {
+        unsafe { ffi::classInit<<structName>>(sample_rate) }
+    }
+    fn instance_reset_params(&mut self) {
+        unsafe { ffi::instanceResetUserInterface<<structName>>(self.dsp) }
+    }
+    fn instance_clear(&mut self) {
+        unsafe { ffi::instanceClear<<structName>>(self.dsp) }
+    }
+    fn instance_constants(&mut self, sample_rate: i32) {
+        unsafe { ffi::instanceConstants<<structName>>(self.dsp, sample_rate) }
+    }
+    fn instance_init(&mut self, sample_rate: i32) {
+        unsafe { ffi::instanceInit<<structName>>(self.dsp, sample_rate) }
+    }
+    fn init(&mut self, sample_rate: i32) {
+        unsafe { ffi::init<<structName>>(self.dsp, sample_rate) }
+    }
+    fn build_user_interface(&self, ui_interface: &mut dyn UI<Self::T>) {
+        let mut glue = ffi::Glue { zones: self.zones.clone(), ui: Some(ui_interface) };
+        unsafe { ffi::buildUserInterface<<structName>>(self.dsp, &mut ffi::ui_glue(&mut glue)) };
+    }
+    fn build_user_interface_static(ui_interface: &mut dyn UI<Self::T>) {
+        FaustDsp::build_user_interface(&Self::new(), ui_interface);
+    }
+    fn get_param(&self, param: ParamIndex) -> Option<Self::T> {
+        if param.0 < 0 {
+            return None;
+        }
+        let zone = *self.zones.get(param.0 as usize)?;
+        if zone.is_null() {
+            None
+        } else {
+            Some(unsafe { *zone })
+        }
+    }
+    fn set_param(&mut self, param: ParamIndex, value: Self::T) {
+        if param.0 < 0 {
+            return;
+        }
+        if let Some(zone) = self.zones.get(param.0 as usize) {
+            if !zone.is_null() {
+                unsafe { **zone = value }
+            }
+        }
+    }
+    fn compute(&mut self, count: i32, inputs: &[&[Self::T]], outputs: &mut [&mut [Self::T]]) {
+        assert!(count >= 0, "negative count");
+        let len = count as usize;
+        assert_eq!(inputs.len(), FAUST_INPUTS, "wrong number of inputs");
+        assert_eq!(outputs.len(), FAUST_OUTPUTS, "wrong number of outputs");
+        for (ptr, input) in self.inputs.iter_mut().zip(inputs) {
+            assert!(input.len() >= len, "input shorter than count");
+            // The C code never writes to its inputs.
+            *ptr = input.as_ptr() as *mut FaustFloat;
+        }
+        for (ptr, output) in self.outputs.iter_mut().zip(outputs.iter_mut()) {
+            assert!(output.len() >= len, "output shorter than count");
+            *ptr = output.as_mut_ptr();
+        }
+        unsafe {
+            ffi::compute<<structName>>(
+                self.dsp,
+                count,
+                self.inputs.as_mut_ptr(),
+                self.outputs.as_mut_ptr(),
+            );
+        }
+    }
+}
+"#;
+
+/// Finds the number returned by a `getNumInputs` or `getNumOutputs` function.
+fn returned_channels(code: &str, function: &str) -> String {
+    let start = code
+        .find(function)
+        .unwrap_or_else(|| panic!("{} not found in generated code", function));
+    let rest = &code[start..];
+    let value = rest
+        .find("return")
+        .and_then(|ret| Some(&rest[ret + "return".len()..ret + rest[ret..].find(';')?]))
+        .unwrap_or_else(|| panic!("{} has no return statement", function));
+    let value = value.trim();
+    assert!(
+        value.parse::<usize>().is_ok(),
+        "{} does not return a constant",
+        function
+    );
+    value.to_owned()
+}
+
+/// Counts the widgets of the json description, returns the active and passive count.
+fn widget_counts(items: &[LayoutItem]) -> (usize, usize) {
+    items
+        .iter()
+        .fold((0, 0), |(actives, passives), item| match item {
+            LayoutItem::HGroup { items, .. }
+            | LayoutItem::VGroup { items, .. }
+            | LayoutItem::TGroup { items, .. } => {
+                let (group_actives, group_passives) = widget_counts(items);
+                (actives + group_actives, passives + group_passives)
+            }
+            LayoutItem::Button { .. }
+            | LayoutItem::CheckBox { .. }
+            | LayoutItem::VSlider { .. }
+            | LayoutItem::HSlider { .. }
+            | LayoutItem::NEntry { .. } => (actives + 1, passives),
+            LayoutItem::VBarGraph { .. } | LayoutItem::HBarGraph { .. } => (actives, passives + 1),
+            LayoutItem::Soundfile { .. } => (actives, passives),
+        })
+}
+
+/// Whether the json description contains a soundfile.
+fn has_soundfile(items: &[LayoutItem]) -> bool {
+    items.iter().any(|item| match item {
+        LayoutItem::HGroup { items, .. }
+        | LayoutItem::VGroup { items, .. }
+        | LayoutItem::TGroup { items, .. } => has_soundfile(items),
+        LayoutItem::Soundfile { .. } => true,
+        _ => false,
+    })
+}
+
+/// Panics if an architecture file is used with the C or C++ backend.
+pub(crate) fn assert_architecture(builder: &FaustBuilder, backend: Backend) {
+    assert!(
+        backend == Backend::Rust || !matches!(builder.get_architecture(), Architecture::File(_)),
+        "architecture files are not supported by the {} backend",
+        backend.lang(),
+    );
+}
+
+/// Compiles the C or C++ code generated by faust and returns the Rust wrapper.
+///
+/// The code of the Rust backend is returned unchanged.
+pub(crate) fn wrap(builder: &FaustBuilder, backend: Backend, dsp_code: &str) -> String {
+    let (extension, shim) = match backend {
+        Backend::Rust => return dsp_code.to_owned(),
+        Backend::C => ("c", C_SHIM),
+        Backend::Cpp => ("cpp", CPP_SHIM),
+    };
+    assert_architecture(builder, backend);
+    assert!(
+        builder
+            .get_code_option(&CodeOptionDiscriminants::Double)
+            .is_none(),
+        "the {} backend wrapper uses FaustFloat = f32, -double is not supported",
+        backend.lang(),
+    );
+    let out_dir: PathBuf = env::var_os("OUT_DIR")
+        .expect("the C backend can only be used in build scripts, OUT_DIR is not defined")
+        .into();
+    let struct_name = builder.get_struct_name();
+    let mut values = HashMap::new();
+    values.insert("structName".to_owned(), struct_name.clone());
+    values.insert("lang".to_owned(), backend.lang().to_owned());
+    values.insert(
+        "inputs".to_owned(),
+        returned_channels(dsp_code, "getNumInputs"),
+    );
+    values.insert(
+        "outputs".to_owned(),
+        returned_channels(dsp_code, "getNumOutputs"),
+    );
+    let json_path = builder.get_json_path();
+    let json = fs::read_to_string(&json_path).unwrap_or_else(|err| {
+        panic!(
+            "json file not found at path {}: {}",
+            json_path.display(),
+            err
+        )
+    });
+    let faust_json: FaustJson =
+        serde_json::from_str(&json).unwrap_or_else(|err| panic!("json parsing error: {}", err));
+    assert!(
+        !has_soundfile(&faust_json.ui),
+        "soundfiles are not supported by the {} backend wrapper",
+        backend.lang(),
+    );
+    let (actives, passives) = widget_counts(&faust_json.ui);
+    values.insert("actives".to_owned(), actives.to_string());
+    values.insert("passives".to_owned(), passives.to_string());
+
+    let code_path = out_dir.join(format!("{struct_name}.{extension}"));
+    fs::write(&code_path, dsp_code).expect("failed to write generated code");
+    let shim_path = out_dir.join(format!("{struct_name}_shim.{extension}"));
+    fs::write(&shim_path, template::render(shim, &values)).expect("failed to write shim");
+    cc::Build::new()
+        .cpp(backend == Backend::Cpp)
+        .file(&shim_path)
+        .include(builder.get_include_dir())
+        .include(&out_dir)
+        .warnings(false)
+        .compile(&format!("faust_{struct_name}"));
+
+    template::render(RUST_WRAPPER, &values)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{has_soundfile, widget_counts};
+    use faust_json::LayoutItem;
+
+    #[test]
+    fn counts_widgets_in_groups() {
+        let items: Vec<LayoutItem> = serde_json::from_str(
+            r#"[{"type":"vgroup","label":"synth","items":[
+                {"type":"button","label":"gate","shortname":"gate","address":"/synth/gate","varname":"fButton0"},
+                {"type":"hgroup","label":"osc","items":[
+                    {"type":"hslider","label":"freq","shortname":"freq","address":"/synth/osc/freq",
+                     "varname":"fHslider0","init":440,"min":20,"max":2000,"step":1},
+                    {"type":"checkbox","label":"on","shortname":"on","address":"/synth/osc/on",
+                     "varname":"fCheckbox0"},
+                    {"type":"nentry","label":"wave","shortname":"wave","address":"/synth/osc/wave",
+                     "varname":"fEntry0","init":0,"min":0,"max":3,"step":1}]},
+                {"type":"soundfile","label":"sample","url":"{'a.wav'}",
+                 "address":"/synth/sample","varname":"fSoundfile0"},
+                {"type":"vbargraph","label":"level","shortname":"level","address":"/synth/level",
+                 "varname":"fVbargraph0","min":0,"max":1}]}]"#,
+        )
+        .expect("valid json");
+        assert_eq!(widget_counts(&items), (4, 1));
+    }
+
+    #[test]
+    fn counts_nothing_without_widgets() {
+        assert_eq!(widget_counts(&[]), (0, 0));
+    }
+
+    #[test]
+    fn finds_soundfiles_in_groups() {
+        let items: Vec<LayoutItem> = serde_json::from_str(
+            r#"[{"type":"vgroup","label":"synth","items":[
+                {"type":"hgroup","label":"osc","items":[
+                    {"type":"soundfile","label":"sample","url":"{'a.wav'}",
+                     "address":"/synth/osc/sample","varname":"fSoundfile0"}]}]}]"#,
+        )
+        .expect("valid json");
+        assert!(has_soundfile(&items));
+        assert!(!has_soundfile(&[]));
+    }
+}
@@ -0,0 +1,287 @@
+//! Compare the output of the Rust backend with the C backend of faust.
+//!
+//! The C version of a dsp is built in `build.rs` with [`build_c_reference`],
+//! the Rust version as usual with [`FaustBuilder`]:
+//! ```no_run
+//! # use faust_build::builder::FaustBuilder;
+//! # use std::{env, path::PathBuf};
+//! // build.rs
+//! # let out_dir = PathBuf::from(env::var_os("OUT_DIR").expect("OUT_DIR is set by cargo"));
+//! FaustBuilder::default_for_file("dsp/volume.dsp", out_dir.join("volume.rs")).build();
+//! faust_build::compare::build_c_reference("dsp/volume.dsp", "VolumeC");
+//! ```
+//! The tests include both versions, so this part can only be compiled with the files built above:
+//! ```ignore
+//! mod rust { include!(concat!(env!("OUT_DIR"), "/volume.rs")); }
+//! mod c { include!(concat!(env!("OUT_DIR"), "/volume_c.rs")); }
+//! let report = faust_build::compare::compare::<rust::Volume, c::VolumeC>(44_100);
+//! assert!(report.max_deviation() < 1e-5, "{}", report);
+//! ```
+
+use crate::{builder::FaustBuilder, c_backend::Backend, code_option::CodeOption};
+use faust_types::{FaustDsp, ParamIndex, UI};
+use heck::SnakeCase;
+use std::{
+    convert::TryFrom,
+    env,
+    fmt::{self, Display},
+    path::PathBuf,
+};
+
+const BLOCK_SIZE: usize = 64;
+const SIGNAL_LENGTH: usize = 4096;
+const SWEEP_STEPS: usize = 16;
+
+/// Compiles `dsp_path` with the C backend into a struct named `struct_name`.
+///
+/// Must be called from a build script. The wrapper is written to
+/// `OUT_DIR/<struct_name in snake case>.rs`, its path is returned.
+pub fn build_c_reference(dsp_path: impl Into<PathBuf>, struct_name: &str) -> PathBuf {
+    let out_dir: PathBuf = env::var_os("OUT_DIR")
+        .expect("Environment Variable OUT_DIR is not defined")
+        .into();
+    let out_path = out_dir.join(format!("{}.rs", struct_name.to_snake_case()));
+    let mut b = FaustBuilder::default();
+    b.set_dsp_path(dsp_path);
+    b.set_out_path(&out_path);
+    b.set_code_option(CodeOption::StructName(struct_name.to_owned()));
+    b.set_backend(Backend::C);
+    b.build();
+    out_path
+}
+
+/// An active widget of the dsp.
+#[derive(Debug, Clone)]
+struct Param {
+    label: String,
+    index: ParamIndex,
+    min: f32,
+    max: f32,
+}
+
+/// Collects the active widgets of a dsp.
+#[derive(Default)]
+struct ParamCollector(Vec<Param>);
+
+impl ParamCollector {
+    fn push(&mut self, label: &str, index: ParamIndex, min: f32, max: f32) {
+        self.0.push(Param {
+            label: label.to_owned(),
+            index,
+            min,
+            max,
+        });
+    }
+}
+
+impl UI<f32> for ParamCollector {
+    fn add_button(&mut self, label: &str, param: ParamIndex) {
+        self.push(label, param, 0.0, 1.0);
+    }
+    fn add_check_button(&mut self, label: &str, param: ParamIndex) {
+        self.push(label, param, 0.0, 1.0);
+    }
+    fn add_vertical_slider(
+        &mut self,
+        label: &str,
+        param: ParamIndex,
+        _init: f32,
+        min: f32,
+        max: f32,
+        _step: f32,
+    ) {
+        self.push(label, param, min, max);
+    }
+    fn add_horizontal_slider(
+        &mut self,
+        label: &str,
+        param: ParamIndex,
+        _init: f32,
+        min: f32,
+        max: f32,
+        _step: f32,
+    ) {
+        self.push(label, param, min, max);
+    }
+    fn add_num_entry(
+        &mut self,
+        label: &str,
+        param: ParamIndex,
+        _init: f32,
+        min: f32,
+        max: f32,
+        _step: f32,
+    ) {
+        self.push(label, param, min, max);
+    }
+}
+
+fn params(dsp: &impl FaustDsp<T = f32>) -> Vec<Param> {
+    let mut params = ParamCollector::default();
+    dsp.build_user_interface(&mut params);
+    params.0
+}
+
+/// Largest difference between the outputs of both backends for one test signal.
+#[derive(Debug, Clone)]
+pub struct Deviation {
+    pub signal: String,
+    pub max: f32,
+    pub channel: usize,
+    pub frame: usize,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Report {
+    pub deviations: Vec<Deviation>,
+}
+
+impl Report {
+    /// The largest deviation of all test signals.
+    #[must_use]
+    pub fn max_deviation(&self) -> f32 {
+        self.deviations.iter().map(|d| d.max).fold(0.0, f32::max)
+    }
+}
+
+impl Display for Report {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for d in &self.deviations {
+            writeln!(
+                f,
+                "{}: max deviation {:e} at channel {} frame {}",
+                d.signal, d.max, d.channel, d.frame
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// Deterministic white noise in `-1..1`.
+fn noise(channels: usize, len: usize, seed: u32) -> Vec<Vec<f32>> {
+    let mut state = seed;
+    (0..channels)
+        .map(|_| {
+            (0..len)
+                .map(|_| {
+                    state = state.wrapping_mul(1_664_525).wrapping_add(1_013_904_223);
+                    #[allow(clippy::cast_precision_loss)]
+                    let n = (state >> 8) as f32 / (1 << 24) as f32;
+                    n.mul_add(2.0, -1.0)
+                })
+                .collect()
+        })
+        .collect()
+}
+
+struct Runner<'a, R, C> {
+    rust: &'a mut R,
+    c: &'a mut C,
+    deviation: Deviation,
+    frame: usize,
+}
+
+impl<R, C> Runner<'_, R, C>
+where
+    R: FaustDsp<T = f32>,
+    C: FaustDsp<T = f32>,
+{
+    fn process(&mut self, inputs: &[Vec<f32>], start: usize, len: usize) {
+        let inputs: Vec<&[f32]> = inputs.iter().map(|i| &i[start..start + len]).collect();
+        let channels = usize::try_from(self.c.get_num_outputs()).expect("negative channel count");
+        let mut rust_out = vec![vec![0.0; len]; channels];
+        let mut c_out = vec![vec![0.0; len]; channels];
+        let mut rust_refs: Vec<&mut [f32]> = rust_out.iter_mut().map(Vec::as_mut_slice).collect();
+        let mut c_refs: Vec<&mut [f32]> = c_out.iter_mut().map(Vec::as_mut_slice).collect();
+        let count = i32::try_from(len).expect("block too large");
+        self.rust.compute(count, &inputs, &mut rust_refs);
+        self.c.compute(count, &inputs, &mut c_refs);
+        for (channel, (r, c)) in rust_out.iter().zip(&c_out).enumerate() {
+            for (frame, (r, c)) in r.iter().zip(c).enumerate() {
+                let diff = if r.is_nan() && c.is_nan() {
+                    0.0
+                } else if r.is_nan() || c.is_nan() {
+                    f32::INFINITY
+                } else {
+                    (r - c).abs()
+                };
+                if diff > self.deviation.max {
+                    self.deviation.max = diff;
+                    self.deviation.channel = channel;
+                    self.deviation.frame = self.frame + frame;
+                }
+            }
+        }
+        self.frame += len;
+    }
+}
+
+/// Runs the Rust dsp `R` and the C dsp `C` side by side on an impulse, on noise
+/// and on noise while sweeping each active widget from min to max.
+///
+/// # Panics
+/// If both dsps disagree on the number of channels or widgets.
+#[must_use]
+pub fn compare<R, C>(sample_rate: i32) -> Report
+where
+    R: FaustDsp<T = f32>,
+    C: FaustDsp<T = f32>,
+{
+    let mut rust = R::new();
+    let mut c = C::new();
+    let rust_params = params(&rust);
+    let c_params = params(&c);
+    assert_eq!(rust.get_num_inputs(), c.get_num_inputs());
+    assert_eq!(rust.get_num_outputs(), c.get_num_outputs());
+    assert_eq!(rust_params.len(), c_params.len());
+
+    let inputs = usize::try_from(c.get_num_inputs()).expect("negative channel count");
+    let mut impulse = vec![vec![0.0; SIGNAL_LENGTH]; inputs];
+    for channel in &mut impulse {
+        channel[0] = 1.0;
+    }
+    let noise = noise(inputs, SIGNAL_LENGTH, 1);
+
+    let mut report = Report::default();
+    let mut run = |signal: String, test: &mut dyn FnMut(&mut Runner<R, C>)| {
+        rust.init(sample_rate);
+        c.init(sample_rate);
+        let mut runner = Runner {
+            rust: &mut rust,
+            c: &mut c,
+            deviation: Deviation {
+                signal,
+                max: 0.0,
+                channel: 0,
+                frame: 0,
+            },
+            frame: 0,
+        };
+        test(&mut runner);
+        report.deviations.push(runner.deviation);
+    };
+    let whole = |input: &[Vec<f32>]| {
+        let input = input.to_vec();
+        move |runner: &mut Runner<R, C>| {
+            for start in (0..SIGNAL_LENGTH).step_by(BLOCK_SIZE) {
+                runner.process(&input, start, BLOCK_SIZE);
+            }
+        }
+    };
+    run("impulse".into(), &mut whole(&impulse));
+    run("noise".into(), &mut whole(&noise));
+    for (rust_param, c_param) in rust_params.iter().zip(&c_params) {
+        run(format!("sweep {}", rust_param.label), &mut |runner| {
+            for step in 0..=SWEEP_STEPS {
+                #[allow(clippy::cast_precision_loss)]
+                let value = (rust_param.max - rust_param.min)
+                    .mul_add(step as f32 / SWEEP_STEPS as f32, rust_param.min);
+                runner.rust.set_param(rust_param.index, value);
+                runner.c.set_param(c_param.index, value);
+                let start = (step * BLOCK_SIZE) % SIGNAL_LENGTH;
+                runner.process(&noise, start, BLOCK_SIZE);
+            }
+        });
+    }
+    report
+}
@@ -54,6 +54,18 @@ impl Default for CompileOptions {
 impl CompileOptions {
     #[must_use]
     pub fn to_command_args(&self) -> Vec<&OsStr> {
+        let mut r = self.to_option_args();
+        if let Some(dsp_path) = &self.dsp_path {
+            r.push(dsp_path.as_ref());
+        } else {
+            panic!("No Path to DSP file provided")
+        }
+        r
+    }
+
+    /// All arguments except the dsp file itself.
+    #[must_use]
+    pub fn to_option_args(&self) -> Vec<&OsStr> {
         let mut r = Vec::<&OsStr>::new();
         if let Some(arch_file) = self.architecture.get_file_path() {
             r.push("-a".as_ref());
@@ -80,11 +92,6 @@ impl CompileOptions {
             r.push("-t".as_ref());
             r.push(timeout.as_ref());
         }
-        if let Some(dsp_path) = &self.dsp_path {
-            r.push(dsp_path.as_ref());
-        } else {
-            panic!("No Path to DSP file provided")
-        }
         r
     }
 }
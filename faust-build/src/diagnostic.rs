@@ -0,0 +1,119 @@
+//! Messages of the faust compiler.
+
+use std::{
+    error::Error,
+    fmt::{self, Display},
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Severity {
+    Error,
+    Warning,
+}
+
+/// A single message reported by the faust compiler.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Diagnostic {
+    pub severity: Severity,
+    /// The dsp or library file the message refers to.
+    pub file: Option<String>,
+    pub line: Option<u32>,
+    pub message: String,
+}
+
+impl Diagnostic {
+    /// Parses a line in the format used by faust: `file : line : ERROR : message`.
+    /// Lines without location are kept as plain message.
+    #[must_use]
+    pub fn parse(line: &str) -> Self {
+        let parts: Vec<&str> = line.splitn(4, " : ").collect();
+        if let [file, line_nr, severity, message] = parts[..] {
+            if let Ok(line_nr) = line_nr.trim().parse() {
+                let severity = if severity.trim() == "WARNING" {
+                    Severity::Warning
+                } else {
+                    Severity::Error
+                };
+                return Self {
+                    severity,
+                    file: Some(file.trim().to_owned()),
+                    line: Some(line_nr),
+                    message: message.trim().to_owned(),
+                };
+            }
+        }
+        let (severity, message) = line.strip_prefix("WARNING : ").map_or_else(
+            || {
+                (
+                    Severity::Error,
+                    line.strip_prefix("ERROR : ").unwrap_or(line),
+                )
+            },
+            |message| (Severity::Warning, message),
+        );
+        Self {
+            severity,
+            file: None,
+            line: None,
+            message: message.trim().to_owned(),
+        }
+    }
+}
+
+impl Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let severity = match self.severity {
+            Severity::Error => "ERROR",
+            Severity::Warning => "WARNING",
+        };
+        match (&self.file, self.line) {
+            (Some(file), Some(line)) => {
+                write!(f, "{file} : {line} : {severity} : {}", self.message)
+            }
+            _ => write!(f, "{severity} : {}", self.message),
+        }
+    }
+}
+
+/// Compilation failed. Contains everything libfaust reported.
+#[derive(Debug, Clone)]
+pub struct LibFaustError {
+    pub diagnostics: Vec<Diagnostic>,
+}
+
+impl LibFaustError {
+    /// Parses every non-empty line of `message` into a [`Diagnostic`].
+    #[must_use]
+    pub fn from_message(message: &str) -> Self {
+        let diagnostics = message
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .map(Diagnostic::parse)
+            .collect();
+        Self { diagnostics }
+    }
+
+    /// A single error without location.
+    #[must_use]
+    pub fn error(message: impl Into<String>) -> Self {
+        Self {
+            diagnostics: vec![Diagnostic {
+                severity: Severity::Error,
+                file: None,
+                line: None,
+                message: message.into(),
+            }],
+        }
+    }
+}
+
+impl Display for LibFaustError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for d in &self.diagnostics {
+            writeln!(f, "{d}")?;
+        }
+        Ok(())
+    }
+}
+
+impl Error for LibFaustError {}
@@ -0,0 +1,141 @@
+//! Rebuild a dsp into a dynamic library whenever the dsp file changes.
+//!
+//! The libraries can be loaded with `faust_state::hot_reload::load_dsp`
+//! and swapped into a running `DspHandle`. Meant for development only.
+
+use crate::{architecture::Architecture, builder::FaustBuilder};
+use std::{
+    env::{
+        self,
+        consts::{DLL_PREFIX, DLL_SUFFIX},
+    },
+    fs,
+    panic::{self, AssertUnwindSafe},
+    path::{Path, PathBuf},
+    process::Command,
+    sync::mpsc::{self, Receiver},
+    thread,
+    time::{Duration, SystemTime},
+};
+
+const CRATE_NAME: &str = "faust_hot_reload_dsp";
+
+pub struct DylibBuilder {
+    dsp_path: PathBuf,
+    out_dir: PathBuf,
+    faust_types_dependency: String,
+    build_count: usize,
+}
+
+impl DylibBuilder {
+    /// `out_dir` holds the generated crate and the built libraries.
+    pub fn new(dsp_path: impl Into<PathBuf>, out_dir: impl Into<PathBuf>) -> Self {
+        Self {
+            dsp_path: dsp_path.into(),
+            out_dir: out_dir.into(),
+            faust_types_dependency: default_faust_types_dependency(),
+            build_count: 0,
+        }
+    }
+
+    /// The faust-types dependency of the generated crate as toml value, e.g. `{ path = "../faust-types" }`.
+    /// It has to match the faust-types used by the application loading the library,
+    /// by default it is the faust-types faust-build was built with.
+    pub fn set_faust_types_dependency(&mut self, dependency: impl Into<String>) {
+        self.faust_types_dependency = dependency.into();
+    }
+
+    /// Compiles the dsp into a dynamic library and returns its path.
+    ///
+    /// Every build gets a new file name, as a library can't be loaded twice from the same path.
+    ///
+    /// # Errors
+    /// Returns the error message of faust or cargo.
+    pub fn build(&mut self) -> Result<PathBuf, String> {
+        let src_dir = self.out_dir.join("src");
+        fs::create_dir_all(&src_dir).map_err(|err| err.to_string())?;
+
+        let dsp_path = self.dsp_path.clone();
+        let lib_rs = src_dir.join("lib.rs");
+        panic::catch_unwind(AssertUnwindSafe(|| {
+            let mut b = FaustBuilder::default_for_file(dsp_path, lib_rs);
+            b.set_architecture(Architecture::dylib());
+            b.build();
+        }))
+        .map_err(|err| {
+            err.downcast_ref::<String>()
+                .cloned()
+                .or_else(|| err.downcast_ref::<&str>().map(|s| (*s).to_owned()))
+                .unwrap_or_else(|| "faust compilation failed".to_owned())
+        })?;
+
+        let manifest = format!(
+            "[package]\nname = \"{CRATE_NAME}\"\nversion = \"0.0.0\"\nedition = \"2021\"\n\n\
+             [lib]\ncrate-type = [\"cdylib\"]\n\n\
+             [dependencies]\nfaust-types = {}\n\n\
+             [workspace]\n",
+            self.faust_types_dependency
+        );
+        let manifest_path = self.out_dir.join("Cargo.toml");
+        fs::write(&manifest_path, manifest).map_err(|err| err.to_string())?;
+
+        let cargo = env::var_os("CARGO").unwrap_or_else(|| "cargo".into());
+        let output = Command::new(cargo)
+            .args(["build", "--release", "--manifest-path"])
+            .arg(&manifest_path)
+            .output()
+            .map_err(|err| err.to_string())?;
+        if !output.status.success() {
+            return Err(String::from_utf8_lossy(&output.stderr).into_owned());
+        }
+
+        let built = self
+            .out_dir
+            .join("target/release")
+            .join(format!("{DLL_PREFIX}{CRATE_NAME}{DLL_SUFFIX}"));
+        let target = self.out_dir.join(format!(
+            "{DLL_PREFIX}{CRATE_NAME}_{}{DLL_SUFFIX}",
+            self.build_count
+        ));
+        fs::copy(built, &target).map_err(|err| err.to_string())?;
+        self.build_count += 1;
+        Ok(target)
+    }
+
+    /// Polls the dsp file every `interval` and rebuilds it when it was modified.
+    ///
+    /// Each build result is sent to the returned receiver.
+    /// The watcher stops when the receiver is dropped.
+    #[must_use]
+    pub fn watch(mut self, interval: Duration) -> Receiver<Result<PathBuf, String>> {
+        let (tx, rx) = mpsc::channel();
+        thread::spawn(move || {
+            let mut last_modified = modified(&self.dsp_path);
+            loop {
+                thread::sleep(interval);
+                let modified = modified(&self.dsp_path);
+                if modified == last_modified {
+                    continue;
+                }
+                last_modified = modified;
+                if tx.send(self.build()).is_err() {
+                    break;
+                }
+            }
+        });
+        rx
+    }
+}
+
+fn modified(path: &Path) -> Option<SystemTime> {
+    fs::metadata(path).and_then(|m| m.modified()).ok()
+}
+
+fn default_faust_types_dependency() -> String {
+    let local = Path::new(env!("CARGO_MANIFEST_DIR")).join("../faust-types");
+    if local.exists() {
+        format!("{{ path = '{}' }}", local.display())
+    } else {
+        format!("\"={}\"", faust_types::VERSION)
+    }
+}
@@ -14,11 +14,21 @@ use std::ffi::OsStr;
 
 pub mod architecture;
 pub mod builder;
+#[cfg(feature = "c-backend")]
+pub mod c_backend;
 pub mod code_option;
+#[cfg(feature = "compare")]
+pub mod compare;
 pub mod compile_options;
+pub mod diagnostic;
 pub mod dsp_path;
+#[cfg(feature = "hot-reload")]
+pub mod hot_reload;
+#[cfg(feature = "libfaust")]
+pub mod libfaust;
 #[cfg(feature = "faust-ui")]
 pub mod macro_lib;
+pub mod template;
 
 /// Trait to transform a Vector o`FaustArgs`gs into a Vector`OsStr`sStr references.
 ///
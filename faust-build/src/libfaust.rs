@@ -0,0 +1,260 @@
+//! In-process compilation through the C API of libfaust.
+//!
+//! Instead of spawning the `faust` binary and scraping its output, the compiler
+//! is linked as a library and called directly. Errors are returned as
+//! [`Diagnostic`]s instead of panicking on the content of stderr.
+//!
+//! libfaust keeps its state in globals, compilations in the same process,
+//! like concurrent proc-macros, are serialized by a lock.
+
+pub use crate::diagnostic::{Diagnostic, LibFaustError, Severity};
+use std::{
+    convert::TryFrom,
+    ffi::{CStr, CString, OsStr},
+    fs,
+    os::raw::{c_char, c_int, c_void},
+    path::Path,
+    ptr,
+    sync::{Mutex, MutexGuard, PoisonError},
+};
+
+/// libfaust writes at most 4096 bytes into the error buffer.
+const ERROR_MSG_SIZE: usize = 4096;
+
+/// Held while libfaust is used, its context and compiler state are global.
+static LIBFAUST: Mutex<()> = Mutex::new(());
+
+/// The WebAssembly module and JSON description created by libfaust.
+#[repr(C)]
+struct WasmModule {
+    _private: [u8; 0],
+}
+
+#[link(name = "faust")]
+extern "C" {
+    fn createLibContext();
+    fn destroyLibContext();
+    fn CDSPToBoxes(
+        name_app: *const c_char,
+        dsp_content: *const c_char,
+        argc: c_int,
+        argv: *const *const c_char,
+        inputs: *mut c_int,
+        outputs: *mut c_int,
+        error_msg: *mut c_char,
+    ) -> *mut c_void;
+    fn createCSourceFromBoxes(
+        name_app: *const c_char,
+        box_: *mut c_void,
+        lang: *const c_char,
+        argc: c_int,
+        argv: *const *const c_char,
+        error_msg: *mut c_char,
+    ) -> *mut c_char;
+    fn freeCMemory(ptr: *mut c_void);
+    fn createWasmCDSPFactoryFromString(
+        name_app: *const c_char,
+        dsp_content: *const c_char,
+        argc: c_int,
+        argv: *const *const c_char,
+        error_msg: *mut c_char,
+        internal_memory: bool,
+    ) -> *mut WasmModule;
+    fn getWasmCHelpers(module: *mut WasmModule) -> *const c_char;
+    fn freeWasmCModule(module: *mut WasmModule);
+    fn getCLibFaustVersion() -> *const c_char;
+}
+
+/// Version of the linked libfaust.
+#[must_use]
+pub fn version() -> String {
+    let _lock = lock();
+    // SAFETY: libfaust returns a pointer to a static, nul terminated string.
+    unsafe { CStr::from_ptr(getCLibFaustVersion()) }
+        .to_string_lossy()
+        .into_owned()
+}
+
+/// Result of a successful in-process compilation.
+#[derive(Debug, Clone)]
+pub struct LibFaustOutput {
+    /// The generated code.
+    pub code: String,
+    /// The JSON description, if it was requested with `-json`.
+    pub json: Option<String>,
+    /// Warnings reported by libfaust.
+    pub warnings: Vec<Diagnostic>,
+}
+
+fn lock() -> MutexGuard<'static, ()> {
+    LIBFAUST.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+fn to_c_string(s: &OsStr) -> Result<CString, LibFaustError> {
+    let s = s.to_str().ok_or_else(|| {
+        LibFaustError::error(format!("argument is not utf8: {}", s.to_string_lossy()))
+    })?;
+    CString::new(s)
+        .map_err(|_| LibFaustError::error(format!("argument contains a nul byte: {s:?}")))
+}
+
+/// The messages libfaust wrote into `error_msg`, which is cleared for the next call.
+fn error_message(error_msg: &mut [c_char]) -> LibFaustError {
+    // SAFETY: libfaust nul terminates the message, the buffer was zeroed beforehand
+    // and its last byte is never written.
+    let message = unsafe { CStr::from_ptr(error_msg.as_ptr()) }.to_string_lossy();
+    let error = LibFaustError::from_message(&message);
+    error_msg.fill(0);
+    error
+}
+
+/// The warnings of a successful call, libfaust reports them like errors.
+fn warnings(error_msg: &mut [c_char]) -> impl Iterator<Item = Diagnostic> {
+    error_message(error_msg)
+        .diagnostics
+        .into_iter()
+        .map(|diagnostic| Diagnostic {
+            severity: Severity::Warning,
+            ..diagnostic
+        })
+}
+
+/// Initializes the libfaust context for the box API and destroys it when dropped.
+///
+/// Only created while [`LIBFAUST`] is locked.
+struct LibContext;
+
+impl LibContext {
+    fn new(_lock: &MutexGuard<'static, ()>) -> Self {
+        // SAFETY: paired with `destroyLibContext` in `drop`, the lock prevents
+        // other contexts in the meantime.
+        unsafe { createLibContext() };
+        Self
+    }
+}
+
+impl Drop for LibContext {
+    fn drop(&mut self) {
+        // SAFETY: the context was created in `new`.
+        unsafe { destroyLibContext() };
+    }
+}
+
+/// Compiles `dsp_path` with the given command line options.
+///
+/// The options are the same as for the faust binary except that the dsp file is passed separately.
+/// The dsp is compiled from its content and the code is returned as string by libfaust, no
+/// output file is written. With `-json` the JSON description is returned as well, taken from
+/// the helpers of the WebAssembly backend, so libfaust needs to be built with it.
+///
+/// # Errors
+/// Returns the diagnostics reported by libfaust if the compilation failed, or a single
+/// diagnostic if the dsp file or an argument could not be used.
+pub fn compile(dsp_path: &Path, args: &[&OsStr]) -> Result<LibFaustOutput, LibFaustError> {
+    let dsp_content = fs::read_to_string(dsp_path).map_err(|err| {
+        LibFaustError::error(format!("failed to read {}: {err}", dsp_path.display()))
+    })?;
+    let dsp_content = CString::new(dsp_content)
+        .map_err(|_| LibFaustError::error("dsp file contains a nul byte"))?;
+    let name_app = to_c_string(dsp_path.as_os_str())?;
+    let mut lang = CString::new("rust").expect("no nul byte");
+    let mut json = false;
+    let mut c_args: Vec<CString> = Vec::with_capacity(args.len());
+    let mut args_iter = args.iter();
+    while let Some(arg) = args_iter.next() {
+        if *arg == "-lang" {
+            if let Some(value) = args_iter.next() {
+                lang = to_c_string(value)?;
+            }
+        } else if *arg == "-json" {
+            // Would make libfaust write the file, the description is taken from memory instead.
+            json = true;
+        } else {
+            c_args.push(to_c_string(arg)?);
+        }
+    }
+    let arg_ptrs: Vec<*const c_char> = c_args.iter().map(|arg| arg.as_ptr()).collect();
+    let arg_count =
+        c_int::try_from(arg_ptrs.len()).map_err(|_| LibFaustError::error("too many arguments"))?;
+    let mut error_msg = vec![0 as c_char; ERROR_MSG_SIZE + 1];
+    let mut warnings_found = Vec::new();
+
+    let lock = lock();
+    let code = {
+        let _context = LibContext::new(&lock);
+        let (mut inputs, mut outputs): (c_int, c_int) = (0, 0);
+        // SAFETY: all pointers are valid, nul terminated strings that outlive the call,
+        // error_msg is larger than what libfaust writes.
+        let dsp_box = unsafe {
+            CDSPToBoxes(
+                name_app.as_ptr(),
+                dsp_content.as_ptr(),
+                arg_count,
+                arg_ptrs.as_ptr(),
+                ptr::addr_of_mut!(inputs),
+                ptr::addr_of_mut!(outputs),
+                error_msg.as_mut_ptr(),
+            )
+        };
+        if dsp_box.is_null() {
+            return Err(error_message(&mut error_msg));
+        }
+        warnings_found.extend(warnings(&mut error_msg));
+        // SAFETY: as above, `dsp_box` was created in the current context.
+        let source = unsafe {
+            createCSourceFromBoxes(
+                name_app.as_ptr(),
+                dsp_box,
+                lang.as_ptr(),
+                arg_count,
+                arg_ptrs.as_ptr(),
+                error_msg.as_mut_ptr(),
+            )
+        };
+        if source.is_null() {
+            return Err(error_message(&mut error_msg));
+        }
+        warnings_found.extend(warnings(&mut error_msg));
+        // SAFETY: libfaust returns a nul terminated string allocated with its allocator,
+        // which is released with `freeCMemory` after copying it.
+        unsafe {
+            let code = CStr::from_ptr(source).to_string_lossy().into_owned();
+            freeCMemory(source.cast());
+            code
+        }
+    };
+
+    let json = if json {
+        // SAFETY: as above, the factory sets up its own context and the lock is still held.
+        let module = unsafe {
+            createWasmCDSPFactoryFromString(
+                name_app.as_ptr(),
+                dsp_content.as_ptr(),
+                arg_count,
+                arg_ptrs.as_ptr(),
+                error_msg.as_mut_ptr(),
+                false,
+            )
+        };
+        if module.is_null() {
+            return Err(error_message(&mut error_msg));
+        }
+        // SAFETY: the helpers are a nul terminated string owned by the module,
+        // copied before the module is released.
+        Some(unsafe {
+            let json = CStr::from_ptr(getWasmCHelpers(module))
+                .to_string_lossy()
+                .into_owned();
+            freeWasmCModule(module);
+            json
+        })
+    } else {
+        None
+    };
+    drop(lock);
+    Ok(LibFaustOutput {
+        code,
+        json,
+        warnings: warnings_found,
+    })
+}
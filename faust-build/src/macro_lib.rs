@@ -13,6 +13,7 @@ use crate::{
 };
 use heck::SnakeCase;
 use std::{env, iter::FromIterator, path::PathBuf, str::FromStr};
+use strum::EnumString;
 use syn::{parse::Parse, Error, Expr, ExprArray, ExprPath, LitStr, Token};
 
 fn get_flags_token(ts: proc_macro2::TokenStream) -> Vec<String> {
@@ -23,15 +24,55 @@ fn get_flags_token(ts: proc_macro2::TokenStream) -> Vec<String> {
     })
 }
 
+#[cfg(feature = "faust-ui")]
+fn get_ui_options_token(ts: proc_macro2::TokenStream) -> Vec<UiOption> {
+    get_declared_value("ui_options", ts).map_or_else(std::vec::Vec::new, |s| {
+        s.split_whitespace()
+            .map(|name| {
+                UiOption::from_str(name).unwrap_or_else(|_| panic!("unknown ui option {}", name))
+            })
+            .collect()
+    })
+}
+
+/// Options of the generated faust-ui code, given next to the code options
+/// as `[Single, Serde]` to `include!` or as `declare ui_options "Serde";` to `dsp!`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, EnumString)]
+pub enum UiOption {
+    /// Derive `serde::Serialize` and `serde::Deserialize`.
+    Serde,
+    /// Add getters and setters named after the widgets to the dsp struct.
+    Accessors,
+}
+
+#[cfg(feature = "faust-ui")]
+fn ui_options(options: &[UiOption]) -> faust_ui::UiOptions {
+    let mut ui_options = faust_ui::UiOptions::default();
+    for option in options {
+        match option {
+            UiOption::Serde => ui_options.serde = true,
+            UiOption::Accessors => ui_options.accessors = true,
+        }
+    }
+    ui_options
+}
+
 pub struct FileMacroArgs {
     pub dsp_path: LitStr,
     pub flags: Vec<CodeOption>,
+    pub ui_options: Vec<UiOption>,
+}
+
+/// An element of the array of `include!`.
+enum MacroOption {
+    Code(CodeOption),
+    Ui(UiOption),
 }
 
 impl FileMacroArgs {
-    fn parse_enums(input_expr: ExprArray) -> syn::Result<Vec<CodeOption>> {
+    fn parse_enums(input_expr: ExprArray) -> syn::Result<(Vec<CodeOption>, Vec<UiOption>)> {
         let elems = input_expr.elems;
-        elems
+        let options = elems
             .iter()
             .map(|expr| {
                 let Expr::Path(ExprPath { path, .. }) = expr else {
@@ -47,15 +88,26 @@ impl FileMacroArgs {
                         "Can not parse Array Element as CodeGenerationOption Enum Variant",
                     ));
                 };
-                let Ok(fa) = CodeOption::from_str(&name) else {
-                    return Result::Err(Error::new_spanned(
-                        path,
-                        format!("Can not parse Array Element as CodeGenerationOption Enum Variant {name}"),
-                    ));
-                };
-                Ok(fa)
+                CodeOption::from_str(&name)
+                    .map(MacroOption::Code)
+                    .or_else(|_| UiOption::from_str(&name).map(MacroOption::Ui))
+                    .map_err(|_| {
+                        Error::new_spanned(
+                            path,
+                            format!("Can not parse Array Element as CodeGenerationOption or UiOption Enum Variant {name}"),
+                        )
+                    })
             })
-            .collect()
+            .collect::<syn::Result<Vec<_>>>()?;
+        let mut flags = Vec::new();
+        let mut ui_options = Vec::new();
+        for option in options {
+            match option {
+                MacroOption::Code(fa) => flags.push(fa),
+                MacroOption::Ui(ui) => ui_options.push(ui),
+            }
+        }
+        Ok((flags, ui_options))
     }
 }
 
@@ -66,11 +118,16 @@ impl Parse for FileMacroArgs {
             Ok(Self {
                 dsp_path,
                 flags: Vec::new(),
+                ui_options: Vec::new(),
             })
         } else {
             let _comma: Token![,] = input.parse()?;
-            let flags = Self::parse_enums(input.parse()?)?;
-            Ok(Self { dsp_path, flags })
+            let (flags, ui_options) = Self::parse_enums(input.parse()?)?;
+            Ok(Self {
+                dsp_path,
+                flags,
+                ui_options,
+            })
         }
     }
 }
@@ -92,7 +149,8 @@ pub fn build_faust_file_from_macro(args: FileMacroArgs) -> proc_macro2::TokenStr
         dsp_path
     );
 
-    let builder = FaustBuilder::default_for_include_macro(dsp_path, flags);
+    let mut builder = FaustBuilder::default_for_include_macro(dsp_path, flags);
+    builder.set_ui_options(ui_options(&args.ui_options));
     builder.build()
 }
 
@@ -104,7 +162,8 @@ pub fn build_dsp_code_from_macro(input: &proc_macro2::TokenStream) -> proc_macro
     let flags = get_flags_token(input.clone());
     let flags = CodeOption::arg_map_from_str_iter(flags.iter());
 
-    let builder = FaustBuilder::default_for_dsp_macro(&faust_code, flags);
+    let mut builder = FaustBuilder::default_for_dsp_macro(&faust_code, flags);
+    builder.set_ui_options(ui_options(&get_ui_options_token(input.clone())));
 
     builder.write_debug_dsp_file(&builder.get_struct_name().to_snake_case());
     let dsp_code = builder.build();
@@ -0,0 +1,112 @@
+//! Placeholder substitution for architecture files.
+//!
+//! Faust itself only fills in `<<includeIntrinsic>>` and `<<includeclass>>`.
+//! Afterwards every `<<name>>` with a known value is replaced,
+//! unknown placeholders are left untouched.
+//!
+//! Sections can be included conditionally:
+//! ```text
+//! <<#if moduleName>>
+//! pub use <<moduleName>>::*;
+//! <<#else>>
+//! // no module
+//! <<#endif>>
+//! ```
+//! A condition is true if the value exists and is neither empty, `0` nor `false`.
+//! `<<#if !name>>` negates the condition, sections can be nested.
+
+use std::collections::HashMap;
+
+const IF: &str = "<<#if ";
+const ELSE: &str = "<<#else>>";
+const ENDIF: &str = "<<#endif>>";
+
+fn is_true(values: &HashMap<String, String>, condition: &str) -> bool {
+    let condition = condition.trim();
+    if let Some(condition) = condition.strip_prefix('!') {
+        return !is_true(values, condition);
+    }
+    values
+        .get(condition)
+        .is_some_and(|v| !v.is_empty() && v != "0" && v != "false")
+}
+
+/// Finds the `<<#else>>` and `<<#endif>>` belonging to an `<<#if>>` whose body starts at the beginning of `s`.
+/// Returns (`else` position, `endif` position).
+fn find_section_end(s: &str) -> (Option<usize>, usize) {
+    let mut depth = 0;
+    let mut else_pos = None;
+    let mut pos = 0;
+    while let Some(offset) = s[pos..].find("<<#") {
+        let at = pos + offset;
+        let rest = &s[at..];
+        if rest.starts_with(IF) {
+            depth += 1;
+        } else if rest.starts_with(ELSE) && depth == 0 {
+            else_pos = Some(at);
+        } else if rest.starts_with(ENDIF) {
+            if depth == 0 {
+                return (else_pos, at);
+            }
+            depth -= 1;
+        }
+        pos = at + 3;
+    }
+    panic!("missing <<#endif>> in architecture file");
+}
+
+fn render_sections(template: &str, values: &HashMap<String, String>) -> String {
+    let mut out = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(start) = rest.find(IF) {
+        out.push_str(&rest[..start]);
+        let after_if = &rest[start + IF.len()..];
+        let close = after_if
+            .find(">>")
+            .expect("unterminated <<#if in architecture file");
+        let condition = &after_if[..close];
+        let body = &after_if[close + 2..];
+        let (else_pos, endif_pos) = find_section_end(body);
+        let (then_part, else_part) = else_pos.map_or((&body[..endif_pos], ""), |e| {
+            (&body[..e], &body[e + ELSE.len()..endif_pos])
+        });
+        let section = if is_true(values, condition) {
+            then_part
+        } else {
+            else_part
+        };
+        out.push_str(&render_sections(section, values));
+        rest = &body[endif_pos + ENDIF.len()..];
+    }
+    out.push_str(rest);
+    out
+}
+
+/// Replaces the placeholders from left to right, inserted values are not expanded again.
+fn render_placeholders(template: &str, values: &HashMap<String, String>) -> String {
+    let mut out = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(start) = rest.find("<<") {
+        out.push_str(&rest[..start]);
+        rest = &rest[start..];
+        let value = rest[2..]
+            .find(">>")
+            .and_then(|end| Some((end, values.get(&rest[2..2 + end])?)));
+        if let Some((end, value)) = value {
+            out.push_str(value);
+            rest = &rest[2 + end + 2..];
+        } else {
+            out.push('<');
+            rest = &rest[1..];
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+/// Resolves conditional sections and replaces all placeholders with known values.
+#[must_use]
+#[allow(clippy::implicit_hasher)]
+pub fn render(template: &str, values: &HashMap<String, String>) -> String {
+    render_placeholders(&render_sections(template, values), values)
+}
@@ -0,0 +1,137 @@
+use faust_build::{
+    architecture::{AssociatedConsts, FaustDspImpl, ObjectInterface},
+    builder::FaustBuilder,
+};
+use proc_macro2::TokenStream;
+use quote::quote;
+
+fn apply(code: TokenStream) -> String {
+    AssociatedConsts
+        .apply(&FaustBuilder::default(), &code)
+        .to_string()
+}
+
+#[test]
+fn implements_consts_next_to_faust_dsp() {
+    let out = apply(quote! {
+        pub mod dsp {
+            impl FaustDsp for Volume { type T = f32; fn new() -> Self { Self } }
+            impl Volume { fn new() -> Self { Self } }
+        }
+    });
+    let expected = quote! {
+        pub mod dsp {
+            impl FaustDsp for Volume { type T = f32; fn new() -> Self { Self } }
+            impl FaustDspConsts for Volume {
+                const INPUTS: usize = FAUST_INPUTS;
+                const OUTPUTS: usize = FAUST_OUTPUTS;
+                const ACTIVES: usize = FAUST_ACTIVES;
+                const PASSIVES: usize = FAUST_PASSIVES;
+            }
+            impl Volume { fn new() -> Self { Self } }
+        }
+    };
+    assert_eq!(out, expected.to_string());
+}
+
+#[test]
+fn keeps_the_path_and_generics_of_the_impl() {
+    let out = apply(quote! {
+        impl<F: Fn() -> f32> faust_types::FaustDsp for Volume<F> where F: Send { type T = f32; }
+    });
+    let header = quote! {
+        impl<F: Fn() -> f32> faust_types::FaustDspConsts for Volume<F> where F: Send
+    };
+    assert!(out.contains(&header.to_string()), "{}", out);
+}
+
+#[test]
+fn keeps_existing_consts() {
+    let code = quote! {
+        impl FaustDsp for Volume { type T = f32; }
+        mod consts { impl FaustDspConsts for super::Volume { const INPUTS: usize = 1; } }
+    };
+    assert_eq!(apply(code.clone()), code.to_string());
+}
+
+#[test]
+fn ignores_other_traits() {
+    let code = quote! {
+        impl Default for Volume { fn default() -> Self { Self } }
+        impl<T> From<T> for Volume { fn from(_: T) -> Self { Self } }
+    };
+    assert_eq!(apply(code.clone()), code.to_string());
+}
+
+#[test]
+fn default_architecture_adds_consts() {
+    let code = quote! { impl FaustDsp for Volume { type T = f32; } };
+    let out = faust_build::architecture::default(&FaustBuilder::default(), &code).to_string();
+    assert!(out.contains(&quote! { impl FaustDspConsts for Volume }.to_string()));
+}
+
+#[test]
+fn leaves_pipelines_unchanged() {
+    use faust_build::architecture::{AllowLints, Pipeline};
+
+    // Without the stage the dsp only lacks the optional `FaustDspConsts`.
+    let code = quote! { impl FaustDsp for Volume { type T = f32; } };
+    let out = Pipeline::new()
+        .with(AllowLints)
+        .apply(&FaustBuilder::default(), &code)
+        .to_string();
+    assert!(!out.contains("FaustDspConsts"));
+    assert!(out.contains(&code.to_string()));
+}
+
+#[cfg(feature = "faust-ui")]
+#[test]
+fn documents_dsp_struct() {
+    use faust_build::{architecture::DspDoc, code_option::CodeOption};
+
+    let dir = tempfile::tempdir().expect("temp dir");
+    std::fs::write(
+        dir.path().join("volume.dsp.json"),
+        r#"{"name":"volume","filename":"volume.dsp","version":"2.0","compile_options":"",
+            "size":0,"inputs":2,"outputs":2,"author":"Franz Heinzmann","license":"BSD",
+            "meta":[{"description":"Stereo volume control"},{"version":"1.0"}],"ui":[]}"#,
+    )
+    .expect("json written");
+    let mut builder = FaustBuilder::default();
+    builder.set_dsp_path(dir.path().join("volume.dsp"));
+    builder.set_code_option(CodeOption::StructName("Volume".into()));
+    let out = DspDoc.apply(
+        &builder,
+        &quote! {
+            #![allow(dead_code)]
+            #[repr(C)]
+            pub struct Volume { fVslider0: F32 }
+            impl Volume { fn new() -> Volume { Volume { fVslider0: 0.0 } } }
+        },
+    );
+    let expected = quote! {
+        #![allow(dead_code)]
+        #[doc = " The `volume` dsp."]
+        #[doc = ""]
+        #[doc = " Stereo volume control"]
+        #[doc = ""]
+        #[doc = " - Author: Franz Heinzmann"]
+        #[doc = " - License: BSD"]
+        #[doc = " - Version: 1.0"]
+        #[repr(C)]
+        pub struct Volume { fVslider0: F32 }
+        impl Volume { fn new() -> Volume { Volume { fVslider0: 0.0 } } }
+    };
+    assert_eq!(out.to_string(), expected.to_string());
+}
+
+#[test]
+fn keeps_faust_dsp_impls_in_modules() {
+    let code = quote! {
+        pub mod dsp {
+            impl faust_types::FaustDsp for Volume { type T = f32; }
+        }
+    };
+    let out = FaustDspImpl.apply(&FaustBuilder::default(), &code);
+    assert_eq!(out.to_string(), code.to_string());
+}
@@ -0,0 +1,49 @@
+use faust_build::diagnostic::{Diagnostic, LibFaustError, Severity};
+
+#[test]
+fn parses_located_messages() {
+    let error = Diagnostic::parse("volume.dsp : 3 : ERROR : undefined symbol : gain");
+    assert_eq!(
+        error,
+        Diagnostic {
+            severity: Severity::Error,
+            file: Some("volume.dsp".into()),
+            line: Some(3),
+            message: "undefined symbol : gain".into(),
+        }
+    );
+    let warning = Diagnostic::parse("stdfaust.lib : 12 : WARNING : deprecated");
+    assert_eq!(warning.severity, Severity::Warning);
+    assert_eq!(warning.line, Some(12));
+    assert_eq!(
+        warning.to_string(),
+        "stdfaust.lib : 12 : WARNING : deprecated"
+    );
+}
+
+#[test]
+fn parses_messages_without_location() {
+    let warning = Diagnostic::parse("WARNING : no output");
+    assert_eq!(warning.severity, Severity::Warning);
+    assert_eq!((warning.file, warning.line), (None, None));
+    assert_eq!(warning.message, "no output");
+
+    let error = Diagnostic::parse("ERROR : a : b : c");
+    assert_eq!(error.severity, Severity::Error);
+    assert_eq!(error.message, "a : b : c");
+
+    let plain = Diagnostic::parse("something went wrong");
+    assert_eq!(plain.severity, Severity::Error);
+    assert_eq!(plain.to_string(), "ERROR : something went wrong");
+}
+
+#[test]
+fn splits_messages_into_lines() {
+    let error = LibFaustError::from_message("a.dsp : 1 : ERROR : first\n\n  \nERROR : second\n");
+    let messages: Vec<&str> = error
+        .diagnostics
+        .iter()
+        .map(|d| d.message.as_str())
+        .collect();
+    assert_eq!(messages, ["first", "second"]);
+}
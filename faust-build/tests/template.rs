@@ -0,0 +1,37 @@
+use faust_build::template::render;
+use std::collections::HashMap;
+
+fn values() -> HashMap<String, String> {
+    [
+        ("moduleName", "dsp"),
+        ("structName", "Volume"),
+        ("inputs", "0"),
+    ]
+    .iter()
+    .map(|(k, v)| ((*k).to_owned(), (*v).to_owned()))
+    .collect()
+}
+
+#[test]
+fn replaces_placeholders() {
+    let out = render(
+        "pub mod <<moduleName>> { <<structName>> <<unknown>> }",
+        &values(),
+    );
+    assert_eq!(out, "pub mod dsp { Volume <<unknown>> }");
+}
+
+#[test]
+fn does_not_expand_inserted_values() {
+    let mut values = values();
+    values.insert("code".to_owned(), "<<structName>> <<moduleName".to_owned());
+    let out = render("<<code>>>> <<<moduleName>>", &values);
+    assert_eq!(out, "<<structName>> <<moduleName>> <dsp");
+}
+
+#[test]
+fn conditional_sections() {
+    let template = "<<#if moduleName>>a<<#if inputs>>b<<#else>>c<<#endif>><<#endif>>\
+                    <<#if !missing>>d<<#else>>e<<#endif>><<#if missing>>f<<#endif>>";
+    assert_eq!(render(template, &values()), "acd");
+}
@@ -90,6 +90,9 @@ pub enum LayoutItem {
         shortname: String,
         address: String,
         varname: String,
+        /// Offset of the zone in the dsp struct, only emitted by the C and C++ backends.
+        #[serde(default)]
+        index: Option<usize>,
         init: f32,
         min: f32,
         max: f32,
@@ -102,6 +105,9 @@ pub enum LayoutItem {
         shortname: String,
         address: String,
         varname: String,
+        /// Offset of the zone in the dsp struct, only emitted by the C and C++ backends.
+        #[serde(default)]
+        index: Option<usize>,
         init: f32,
         min: f32,
         max: f32,
@@ -114,6 +120,9 @@ pub enum LayoutItem {
         shortname: String,
         address: String,
         varname: String,
+        /// Offset of the zone in the dsp struct, only emitted by the C and C++ backends.
+        #[serde(default)]
+        index: Option<usize>,
         init: Option<f32>,
         min: f32,
         max: f32,
@@ -126,6 +135,9 @@ pub enum LayoutItem {
         shortname: String,
         address: String,
         varname: String,
+        /// Offset of the zone in the dsp struct, only emitted by the C and C++ backends.
+        #[serde(default)]
+        index: Option<usize>,
         init: Option<f32>,
         #[serde(default)]
         meta: Vec<Meta>,
@@ -135,6 +147,9 @@ pub enum LayoutItem {
         shortname: String,
         address: String,
         varname: String,
+        /// Offset of the zone in the dsp struct, only emitted by the C and C++ backends.
+        #[serde(default)]
+        index: Option<usize>,
         init: Option<f32>,
         #[serde(default)]
         meta: Vec<Meta>,
@@ -144,6 +159,9 @@ pub enum LayoutItem {
         shortname: String,
         address: String,
         varname: String,
+        /// Offset of the zone in the dsp struct, only emitted by the C and C++ backends.
+        #[serde(default)]
+        index: Option<usize>,
         min: f32,
         max: f32,
         #[serde(default)]
@@ -154,6 +172,9 @@ pub enum LayoutItem {
         shortname: String,
         address: String,
         varname: String,
+        /// Offset of the zone in the dsp struct, only emitted by the C and C++ backends.
+        #[serde(default)]
+        index: Option<usize>,
         min: f32,
         max: f32,
         #[serde(default)]
@@ -165,6 +186,9 @@ pub enum LayoutItem {
         url: String,
         address: String,
         varname: String,
+        /// Offset of the zone in the dsp struct, only emitted by the C and C++ backends.
+        #[serde(default)]
+        index: Option<usize>,
         #[serde(default)]
         meta: Vec<Meta>,
     },
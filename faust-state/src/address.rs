@@ -0,0 +1,217 @@
+//! The addressing scheme of faust's `JSONUI`, `MapUI` and OSC interfaces.
+//!
+//! The address of a widget is `/` followed by the labels of all enclosing groups and the widget,
+//! with the characters reserved by OSC replaced by `_`.
+//! The shortname is the shortest unique end of the address, joined with `_`.
+
+use crate::Node;
+use std::collections::{BTreeMap, HashMap};
+
+/// Characters faust replaces with `_` in addresses.
+const RESERVED: [char; 11] = [' ', '#', '*', ',', '?', '[', ']', '{', '}', '(', ')'];
+
+/// The address faust builds for a widget, see `PathBuilder::buildPath`.
+pub fn build(groups: &[String], label: &str) -> String {
+    let mut address = String::from("/");
+    for group in groups {
+        address.push_str(group);
+        address.push('/');
+    }
+    address.push_str(label);
+    sanitize(&address)
+}
+
+fn sanitize(address: &str) -> String {
+    address
+        .chars()
+        .map(|c| if RESERVED.contains(&c) { '_' } else { c })
+        .collect()
+}
+
+/// The shortnames of the addresses in declaration order, see `PathBuilder::computeShortNames`.
+///
+/// Every address is prefixed with a unique segment, then colliding ends are extended
+/// by one segment at a time until all ends are unique.
+/// Duplicate addresses get one shortname, the one computed for the last declared of them
+/// including the unique segment, like `P2_group_label`. [`AddressMap`] resolves it to the
+/// first declared of them.
+pub fn shortnames(addresses: &[String]) -> HashMap<String, String> {
+    let unique: Vec<String> = addresses
+        .iter()
+        .enumerate()
+        .map(|(n, address)| format!("/P{}{}", n, to_identifier(address)))
+        .collect();
+    let mut levels: BTreeMap<&str, usize> = unique.iter().map(|path| (path.as_str(), 1)).collect();
+    loop {
+        let mut ends: HashMap<&str, &str> = HashMap::new();
+        let mut collisions = Vec::new();
+        for (&path, &level) in &levels {
+            if let Some(other) = ends.insert(cut(path, level), path) {
+                collisions.push(other);
+                collisions.push(path);
+            }
+        }
+        if collisions.is_empty() {
+            break;
+        }
+        collisions.sort_unstable();
+        collisions.dedup();
+        for path in collisions {
+            *levels.entry(path).or_default() += 1;
+        }
+    }
+    addresses
+        .iter()
+        .zip(&unique)
+        .map(|(address, path)| {
+            let shortname = cut(path, levels[path.as_str()]).replace('/', "_");
+            (address.clone(), shortname)
+        })
+        .collect()
+}
+
+/// Replaces every byte that is not alphanumeric or `/`, like faust's `str2ID`.
+fn to_identifier(address: &str) -> String {
+    let mut identifier = String::with_capacity(address.len());
+    for c in address.chars() {
+        if c.is_ascii_alphanumeric() || c == '/' {
+            identifier.push(c);
+        } else {
+            identifier.extend(std::iter::repeat('_').take(c.len_utf8()));
+        }
+    }
+    identifier
+}
+
+/// The last `level` segments of `path`.
+fn cut(path: &str, level: usize) -> &str {
+    path.rmatch_indices('/')
+        .nth(level - 1)
+        .map_or(path, |(i, _)| &path[i + 1..])
+}
+
+/// Finds parameters by address, shortname or unique end of the address.
+///
+/// Of parameters with the same address the first declared one is found.
+#[derive(Debug, Clone, Default)]
+pub struct AddressMap {
+    /// The first parameter declared with each address.
+    by_address: BTreeMap<String, i32>,
+    by_shortname: HashMap<String, i32>,
+}
+
+impl AddressMap {
+    /// `params` in declaration order.
+    pub fn new<'a>(params: impl IntoIterator<Item = (i32, &'a Node)>) -> Self {
+        let mut map = Self::default();
+        for (idx, node) in params {
+            map.by_address.entry(node.address.clone()).or_insert(idx);
+            map.by_shortname
+                .entry(node.shortname.clone())
+                .or_insert(idx);
+        }
+        map
+    }
+
+    /// Looks up `query` as address, as shortname and as the end of a single address.
+    ///
+    /// Reserved characters in `query` are replaced like in addresses.
+    pub fn get(&self, query: &str) -> Option<i32> {
+        let query = sanitize(query);
+        if let Some(idx) = self.by_address.get(&query) {
+            return Some(*idx);
+        }
+        if let Some(idx) = self.by_shortname.get(&query) {
+            return Some(*idx);
+        }
+        let end = format!("/{}", query.trim_start_matches('/'));
+        let mut matches = self
+            .by_address
+            .iter()
+            .filter(|(address, _)| address.ends_with(&end));
+        match (matches.next(), matches.next()) {
+            (Some((_, idx)), None) => Some(*idx),
+            _ => None,
+        }
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&String, i32)> {
+        self.by_address.iter().map(|(address, idx)| (address, *idx))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{build, shortnames};
+    use crate::{
+        test_dsp::{slider, TestDsp},
+        DspHandle, StateHandle,
+    };
+
+    fn addresses(addresses: &[&str]) -> Vec<String> {
+        addresses
+            .iter()
+            .map(|address| (*address).to_owned())
+            .collect()
+    }
+
+    fn state(widgets: &[&'static str]) -> StateHandle<TestDsp> {
+        let widgets = widgets
+            .iter()
+            .map(|path| slider(path, 0.0, 0.0, 1.0, 0.0))
+            .collect();
+        DspHandle::from_dsp(Box::new(TestDsp::with(1, 0.0, widgets))).1
+    }
+
+    #[test]
+    fn builds_addresses() {
+        let groups = ["synth".to_owned(), "osc 1".to_owned()];
+        assert_eq!(build(&groups, "freq [Hz]"), "/synth/osc_1/freq__Hz_");
+        assert_eq!(build(&[], "gain"), "/gain");
+    }
+
+    #[test]
+    fn extends_colliding_shortnames() {
+        let names = shortnames(&addresses(&["/d/g/x", "/d/h/x", "/d/y"]));
+        assert_eq!(names["/d/g/x"], "g_x");
+        assert_eq!(names["/d/h/x"], "h_x");
+        assert_eq!(names["/d/y"], "y");
+    }
+
+    #[test]
+    fn duplicates_share_the_shortname_of_the_last() {
+        let mut list: Vec<&str> = vec!["/d/x"; 12];
+        list.push("/d/y");
+        let names = shortnames(&addresses(&list));
+        assert_eq!(names["/d/x"], "P11_d_x");
+        assert_eq!(names["/d/y"], "y");
+    }
+
+    #[test]
+    fn finds_params_by_path_address_shortname_and_end() {
+        let state = state(&["osc/freq", "lfo/freq", "gain"]);
+        assert_eq!(state.index_of("osc/freq"), Some(0));
+        assert_eq!(state.index_of("/test/lfo/freq"), Some(1));
+        assert_eq!(state.index_of("osc_freq"), Some(0));
+        assert_eq!(state.index_of("gain"), Some(2));
+        assert_eq!(state.index_of("test/gain"), Some(2));
+        // Ends of more than one address are ambiguous.
+        assert_eq!(state.index_of("freq"), None);
+        assert_eq!(state.index_of("/test/osc"), None);
+    }
+
+    #[test]
+    fn finds_the_first_of_duplicate_addresses() {
+        let state = state(&["osc/freq", "osc/freq", "osc/freq"]);
+        let shortname = state.params()[&0].shortname().to_owned();
+        assert_eq!(shortname, "P2_test_osc_freq");
+        assert_eq!(state.params()[&2].shortname(), shortname);
+        for query in ["osc/freq", "/test/osc/freq", shortname.as_str()] {
+            assert_eq!(state.index_of(query), Some(0), "{query}");
+        }
+        assert_eq!(
+            state.params_by_path().collect::<Vec<_>>(),
+            [(&"/test/osc/freq".to_owned(), Some(&0.0))]
+        );
+    }
+}
@@ -0,0 +1,184 @@
+use faust_types::{dynamic::DynFaustDsp, ParamIndex};
+use std::collections::VecDeque;
+
+/// Presses and releases queued by the audio thread until they are applied.
+pub const BUTTON_QUEUE_LEN: usize = 64;
+
+/// A press or release of a button, queued so that both reach the dsp.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ButtonEvent {
+    pub index: i32,
+    pub pressed: bool,
+}
+
+/// Audio thread side of the buttons.
+///
+/// Applies at most one edge per button and block and keeps a pressed button
+/// high for at least `hold` samples, so every press reaches the dsp.
+#[derive(Debug)]
+pub struct Buttons {
+    pending: VecDeque<ButtonEvent>,
+    /// Buttons that changed recently, with the samples left until they may change again.
+    busy: Vec<(i32, usize)>,
+    hold: usize,
+    generation: u32,
+}
+
+impl Buttons {
+    pub fn new(hold: usize) -> Self {
+        Self {
+            pending: VecDeque::with_capacity(BUTTON_QUEUE_LEN),
+            busy: Vec::with_capacity(BUTTON_QUEUE_LEN),
+            hold,
+            generation: 0,
+        }
+    }
+
+    pub fn set_hold(&mut self, hold: usize) {
+        self.hold = hold;
+    }
+
+    /// Queues `events`, dropping those that don't fit into the queue.
+    pub fn queue(&mut self, events: &[ButtonEvent]) {
+        let free = self.pending.capacity() - self.pending.len();
+        self.pending.extend(events.iter().take(free));
+    }
+
+    /// Sets the buttons allowed to change to their next queued value.
+    pub fn apply<T>(&mut self, dsp: &mut T, generation: u32)
+    where
+        T: DynFaustDsp<f32> + ?Sized,
+    {
+        if generation != self.generation {
+            // The indices belong to a dsp that was swapped out.
+            self.pending.clear();
+            self.busy.clear();
+            self.generation = generation;
+        }
+        let Self {
+            pending,
+            busy,
+            hold,
+            ..
+        } = self;
+        pending.retain(|event| {
+            if busy.iter().any(|(index, _)| *index == event.index) || busy.len() == busy.capacity()
+            {
+                return true;
+            }
+            let (value, samples) = if event.pressed {
+                (1.0, (*hold).max(1))
+            } else {
+                // Stay released for at least this block.
+                (0.0, 1)
+            };
+            dsp.set_param(ParamIndex(event.index), value);
+            busy.push((event.index, samples));
+            false
+        });
+    }
+
+    /// Counts the samples of the computed block towards the hold time.
+    pub fn advance(&mut self, count: usize) {
+        self.busy.retain_mut(|(_, samples)| {
+            *samples = samples.saturating_sub(count);
+            *samples > 0
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ButtonEvent, Buttons};
+    use crate::{
+        test_dsp::{button, param, TestDsp},
+        DspHandle,
+    };
+
+    fn press(index: i32) -> ButtonEvent {
+        ButtonEvent {
+            index,
+            pressed: true,
+        }
+    }
+
+    fn release(index: i32) -> ButtonEvent {
+        ButtonEvent {
+            index,
+            pressed: false,
+        }
+    }
+
+    fn dsp() -> TestDsp {
+        TestDsp::with(1, 0.0, vec![button("a"), button("b")])
+    }
+
+    /// The values the dsp sees in each of `blocks` blocks of `count` samples.
+    fn run(buttons: &mut Buttons, dsp: &mut TestDsp, blocks: usize, count: usize) -> Vec<Vec<f32>> {
+        (0..blocks)
+            .map(|_| {
+                buttons.apply(dsp, 0);
+                buttons.advance(count);
+                dsp.values().to_vec()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn applies_press_and_release_in_separate_blocks() {
+        let mut buttons = Buttons::new(0);
+        let mut dsp = dsp();
+        buttons.queue(&[press(0), release(0), press(1), release(1)]);
+        let blocks = run(&mut buttons, &mut dsp, 3, 64);
+        assert_eq!(blocks, [[1.0, 1.0], [0.0, 0.0], [0.0, 0.0]]);
+    }
+
+    #[test]
+    fn keeps_buttons_pressed_for_the_hold_time() {
+        let mut buttons = Buttons::new(100);
+        let mut dsp = dsp();
+        buttons.queue(&[press(0), release(0)]);
+        let blocks = run(&mut buttons, &mut dsp, 3, 64);
+        assert_eq!(blocks, [[1.0, 0.0], [1.0, 0.0], [0.0, 0.0]]);
+    }
+
+    #[test]
+    fn applies_repeated_presses_one_per_block() {
+        let mut buttons = Buttons::new(0);
+        let mut dsp = dsp();
+        buttons.queue(&[press(0), release(0), press(0), release(0)]);
+        let blocks = run(&mut buttons, &mut dsp, 4, 1);
+        assert_eq!(blocks, [[1.0, 0.0], [0.0, 0.0], [1.0, 0.0], [0.0, 0.0]]);
+    }
+
+    #[test]
+    fn drops_the_events_of_a_swapped_out_dsp() {
+        let mut buttons = Buttons::new(0);
+        let mut dsp = dsp();
+        buttons.queue(&[press(0), release(0)]);
+        buttons.apply(&mut dsp, 0);
+        buttons.advance(1);
+        buttons.apply(&mut dsp, 1);
+        assert_eq!(dsp.values(), [1.0, 0.0]);
+        buttons.queue(&[press(1)]);
+        buttons.apply(&mut dsp, 1);
+        assert_eq!(dsp.values(), [1.0, 1.0]);
+    }
+
+    #[test]
+    fn triggers_reach_the_dsp() {
+        let (mut dsp, mut state) = DspHandle::from_dsp(Box::new(dsp()));
+        dsp.set_button_hold(2);
+        state.trigger(param(1)).expect("button index");
+        let mut output = [0.0; 1];
+        let mut seen = Vec::new();
+        for _ in 0..3 {
+            state.update();
+            dsp.update_and_compute(1, &[], &mut [&mut output]);
+            seen.push(dsp.dsp.values()[1]);
+        }
+        assert_eq!(seen, [1.0, 1.0, 0.0]);
+        state.update();
+        assert_eq!(state.get_param(1), Some(&0.0));
+    }
+}
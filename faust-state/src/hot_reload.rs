@@ -0,0 +1,91 @@
+use faust_types::dynamic::{DynFaustDsp, ABI_VERSION};
+use libloading::{Library, Symbol};
+use std::{
+    error::Error,
+    ffi::{c_void, CStr},
+    fmt,
+    os::raw::c_char,
+    path::Path,
+};
+
+/// A dsp loaded from a dynamic library.
+pub type DynDsp = dyn DynFaustDsp<f32> + Send;
+
+/// Name of the `extern "C"` constructor exported by libraries built with faust-build's
+/// `hot-reload` feature. It returns a `Box<Box<DynDsp>>` as thin pointer.
+pub const CREATE_DSP_SYMBOL: &[u8] = b"faust_create_dsp";
+
+/// Name of the `extern "C"` function returning the [`ABI_VERSION`] the library was built with.
+pub const ABI_VERSION_SYMBOL: &[u8] = b"faust_dsp_abi_version";
+
+#[derive(Debug)]
+pub enum LoadError {
+    Library(libloading::Error),
+    /// The library was built with another faust-types or compiler, its dsp can't be used.
+    AbiMismatch {
+        expected: &'static str,
+        found: String,
+    },
+}
+
+impl fmt::Display for LoadError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Library(err) => write!(f, "{err}"),
+            Self::AbiMismatch { expected, found } => write!(
+                f,
+                "library built with faust-types {found}, expected {expected}"
+            ),
+        }
+    }
+}
+
+impl Error for LoadError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            Self::Library(err) => Some(err),
+            Self::AbiMismatch { .. } => None,
+        }
+    }
+}
+
+impl From<libloading::Error> for LoadError {
+    fn from(err: libloading::Error) -> Self {
+        Self::Library(err)
+    }
+}
+
+/// Loads a dynamic library and creates a new instance of the dsp it contains.
+///
+/// The library is never unloaded, instances created from it can live anywhere.
+/// Swap the result into a running [`DspHandle`](crate::DspHandle) with a [`DspSwapper`](crate::DspSwapper).
+///
+/// Before the constructor is called, the [`ABI_VERSION`] exported by the library is compared
+/// with the one of the loading application, libraries built with another version of
+/// faust-types or another compiler are rejected.
+///
+/// # Safety
+/// The library must be built by faust-build's `hot-reload` feature,
+/// its exported functions are trusted to have the expected signatures.
+///
+/// The dsp is a Rust trait object, its vtable and memory layout are only compatible
+/// between code built with the same faust-types and the same compiler. The ABI version check
+/// refuses libraries built otherwise, but can't detect other differences in the build,
+/// such as another global allocator in the library: the returned `Box` is freed
+/// by the allocator of the application.
+pub unsafe fn load_dsp(path: impl AsRef<Path>) -> Result<Box<DynDsp>, LoadError> {
+    let library = Library::new(path.as_ref())?;
+    let abi_version: Symbol<unsafe extern "C" fn() -> *const c_char> =
+        library.get(ABI_VERSION_SYMBOL)?;
+    let found = CStr::from_ptr(abi_version());
+    if found.to_bytes() != ABI_VERSION.as_bytes() {
+        return Err(LoadError::AbiMismatch {
+            expected: ABI_VERSION,
+            found: found.to_string_lossy().into_owned(),
+        });
+    }
+    let create: Symbol<unsafe extern "C" fn() -> *mut c_void> = library.get(CREATE_DSP_SYMBOL)?;
+    let dsp = *Box::from_raw(create().cast::<Box<DynDsp>>());
+    std::mem::forget(library);
+    Ok(dsp)
+}
@@ -15,21 +15,67 @@
 #![allow(clippy::cast_sign_loss)]
 #![allow(deprecated)]
 
-use faust_types::*;
+use address::AddressMap;
+use faust_types::{
+    collect::{LayoutTree, Widget, WidgetKind},
+    dynamic::DynFaustDsp,
+    *,
+};
 use rtrb::{Consumer, Producer, RingBuffer};
 use std::{
     collections::{BTreeMap, HashMap},
+    marker::PhantomData,
     ops::RangeInclusive,
+    sync::mpsc::{self, Receiver},
 };
 
 const DEFAULT_NAME: &str = "rust_faust";
 
+mod address;
+mod button;
+mod meter;
+mod notify;
+mod swap;
+#[cfg(test)]
+mod test_dsp;
+mod validate;
+use button::Buttons;
+pub use button::{ButtonEvent, BUTTON_QUEUE_LEN};
+use meter::MeterSample;
+pub use meter::{Meter, MeterConfig, METER_RING_BLOCKS};
+#[cfg(feature = "stream")]
+pub use notify::ChangeStream;
+use notify::Subscriber;
+pub use notify::{Change, ChangeFilter};
+pub use swap::DspSwapper;
+use swap::HotSwap;
+pub use validate::{ParamError, ValueMode};
+
+#[cfg(feature = "hot-reload")]
+pub mod hot_reload;
+
 #[derive(Debug)]
-pub struct DspHandle<T> {
+pub struct DspHandle<T: ?Sized> {
     dsp: Box<T>,
     dsp_tx: Producer<State>,
     dsp_rx: Consumer<State>,
+    meter_tx: Producer<MeterSample>,
+    /// Indices of the bargraphs of the dsp.
+    passives: Box<[i32]>,
+    buttons: Buttons,
     name: String,
+    generation: u32,
+    hot_swap: Option<HotSwap<T>>,
+}
+
+impl<T> DspHandle<T>
+where
+    T: FaustDspConsts<T = f32> + 'static,
+{
+    /// Number of input channels of the dsp, usable as array length.
+    pub const INPUTS: usize = T::INPUTS;
+    /// Number of output channels of the dsp, usable as array length.
+    pub const OUTPUTS: usize = T::OUTPUTS;
 }
 
 impl<T> DspHandle<T>
@@ -37,14 +83,20 @@ where
     T: FaustDsp<T = f32> + 'static,
 {
     #[must_use]
-    pub fn new() -> (Self, StateHandle) {
+    pub fn new() -> (Self, StateHandle<T>) {
         let dsp = Box::new(T::new());
         Self::from_dsp(dsp)
     }
+}
 
-    pub fn from_dsp(dsp: Box<T>) -> (Self, StateHandle) {
+impl<T> DspHandle<T>
+where
+    T: DynFaustDsp<f32> + ?Sized + 'static,
+{
+    pub fn from_dsp(dsp: Box<T>) -> (Self, StateHandle<T>) {
         let meta = MetaBuilder::from_dsp(&*dsp);
-        let params = ParamsBuilder::from_dsp(&*dsp);
+        let layout = LayoutTree::from_dsp(&*dsp);
+        let params = ParamsBuilder::from_layout(&layout);
         let name = meta
             .get("name")
             .map_or(DEFAULT_NAME, String::as_str)
@@ -52,6 +104,10 @@ where
 
         let (dsp_tx, main_rx) = RingBuffer::new(1).split();
         let (main_tx, dsp_rx) = RingBuffer::new(1).split();
+        let passives = passives_from_layout(&layout, &params);
+        let (meter_tx, meter_rx) =
+            RingBuffer::new(passives.len().max(1) * METER_RING_BLOCKS).split();
+        let meters = meters_from_passives(&passives, MeterConfig::default());
 
         let this = {
             Self {
@@ -59,41 +115,67 @@ where
                 dsp,
                 dsp_tx,
                 dsp_rx,
+                meter_tx,
+                passives,
+                buttons: Buttons::new(0),
+                generation: 0,
+                hot_swap: None,
             }
         };
         let mut state = State {
             updates: HashMap::with_capacity(params.len()),
             state: HashMap::with_capacity(params.len()),
+            button_events: Vec::new(),
+            generation: 0,
+            applied: false,
         };
 
-        let mut params_by_path = BTreeMap::new();
+        let params_by_path = paths_from_layout(&layout, &params);
+        let params_by_address = addresses_from_layout(&layout, &params);
         for (idx, node) in &params {
-            params_by_path.insert(node.path(), *idx);
             state.state.insert(*idx, node.widget_type().init_value());
         }
 
         let state_handle = StateHandle {
             name,
+            dsp_state: state.state.clone(),
             state,
             meta,
+            layout,
             params,
             params_by_path,
+            params_by_address,
+            value_mode: ValueMode::default(),
+            subscribers: Vec::new(),
+            meters,
+            meter_config: MeterConfig::default(),
             main_rx,
             main_tx,
+            meter_rx,
+            dsp: PhantomData,
         };
         (this, state_handle)
     }
 
+    /// Applies the parameters set by the [`StateHandle`], computes the block
+    /// and hands back the state and the values of the bargraphs after the block.
+    ///
+    /// Buttons change at most once per block and stay pressed for at least
+    /// the samples set with [`set_button_hold`](Self::set_button_hold).
     pub fn update_and_compute(
         &mut self,
         count: i32,
         inputs: &[&[f32]],
         outputs: &mut [&mut [f32]],
     ) {
-        let mut state = self.dsp_rx.pop().map_or(None, |state| {
-            self.update_params_from_state(&state);
+        let mut state = self.dsp_rx.pop().map_or(None, |mut state| {
+            state.applied = state.generation == self.generation;
+            if state.applied {
+                self.update_params_from_state(&state);
+            }
             Some(state)
         });
+        self.buttons.apply(&mut *self.dsp, self.generation);
 
         // Potentially improves the performance of SIMD floating-point math
         // by flushing denormals/underflow to zero.
@@ -114,7 +196,21 @@ where
             self.set_fp_status_register(fpsr | mask);
         }
 
-        self.compute(count, inputs, outputs);
+        if let Some(mut hot_swap) = self.hot_swap.take() {
+            hot_swap.compute(
+                &mut self.dsp,
+                &mut self.generation,
+                &mut self.passives,
+                count,
+                inputs,
+                outputs,
+            );
+            self.hot_swap = Some(hot_swap);
+        } else {
+            self.compute(count, inputs, outputs);
+        }
+        self.buttons.advance(count as usize);
+        self.send_meters();
 
         // Reset fp status register to old value
         if let Some(fpsr) = fpsr {
@@ -123,11 +219,36 @@ where
 
         if !self.dsp_tx.is_full() && state.is_some() {
             let mut state = state.take().expect("cannot fail");
-            self.update_state_from_params(&mut state);
+            if state.generation == self.generation {
+                self.update_state_from_params(&mut state);
+            }
             let _ = self.dsp_tx.push(state);
         }
     }
 
+    /// Checked [`update_and_compute`](Self::update_and_compute), see [`FaustDsp::process`].
+    ///
+    /// Blocks without frames are skipped, parameters are exchanged with the next block.
+    ///
+    /// # Errors
+    /// If the buffers don't match the channels of the dsp or differ in length.
+    pub fn update_and_process(
+        &mut self,
+        inputs: &[&[f32]],
+        outputs: &mut [&mut [f32]],
+    ) -> Result<(), ComputeError> {
+        let count = frame_count(
+            self.dsp.get_num_inputs(),
+            self.dsp.get_num_outputs(),
+            inputs,
+            outputs,
+        )?;
+        if count > 0 {
+            self.update_and_compute(count, inputs, outputs);
+        }
+        Ok(())
+    }
+
     // Gets the fp status register.
     // Needed for flushing denormals
     #[allow(unreachable_code)]
@@ -164,11 +285,34 @@ where
         }
     }
 
+    /// Writes the value of every bargraph into the meter ring, dropping them if it is full.
+    fn send_meters(&mut self) {
+        for idx in &*self.passives {
+            if let Some(value) = self.dsp.get_param(ParamIndex(*idx)) {
+                let sample = MeterSample {
+                    generation: self.generation,
+                    index: *idx,
+                    value,
+                };
+                if self.meter_tx.push(sample).is_err() {
+                    return;
+                }
+            }
+        }
+    }
+
+    /// Sets the updated parameters and queues the button events of `state`.
     pub fn update_params_from_state(&mut self, state: &State) {
         for (idx, value) in &state.updates {
             let idx = ParamIndex(*idx);
             self.dsp.set_param(idx, *value);
         }
+        self.buttons.queue(&state.button_events);
+    }
+
+    /// The minimum number of samples a pressed button stays pressed, at least one block.
+    pub fn set_button_hold(&mut self, samples: usize) {
+        self.buttons.set_hold(samples);
     }
 
     pub fn update_state_from_params(&self, state: &mut State) {
@@ -190,6 +334,18 @@ where
         self.dsp.compute(count, inputs, outputs);
     }
 
+    /// Checked [`compute`](Self::compute), see [`FaustDsp::process`].
+    ///
+    /// # Errors
+    /// If the buffers don't match the channels of the dsp or differ in length.
+    pub fn process(
+        &mut self,
+        inputs: &[&[f32]],
+        outputs: &mut [&mut [f32]],
+    ) -> Result<(), ComputeError> {
+        self.dsp.process(inputs, outputs)
+    }
+
     pub fn num_inputs(&self) -> usize {
         self.dsp.get_num_inputs() as usize
     }
@@ -200,6 +356,9 @@ where
 
     pub fn init(&mut self, sample_rate: i32) {
         self.dsp.init(sample_rate);
+        if let Some(hot_swap) = &self.hot_swap {
+            hot_swap.set_sample_rate(sample_rate);
+        }
     }
 }
 
@@ -207,6 +366,14 @@ where
 pub struct State {
     pub state: HashMap<i32, f32>,
     pub updates: HashMap<i32, f32>,
+    /// Presses and releases of buttons in order, applied one per block.
+    pub button_events: Vec<ButtonEvent>,
+    /// Incremented whenever the dsp is swapped.
+    /// States of another generation are not applied to the dsp.
+    pub generation: u32,
+    /// Set by the dsp if it applied the updates and button events. Otherwise
+    /// they are sent again with the next [`StateHandle::update`].
+    pub applied: bool,
 }
 
 impl State {
@@ -214,53 +381,202 @@ impl State {
         self.updates.insert(idx, value);
         self.state.insert(idx, value);
     }
+
+    /// Queues the updates and button events of a state the dsp did not apply,
+    /// values set since it was sent take precedence.
+    fn requeue(&mut self, unapplied: Self) {
+        for (idx, value) in unapplied.updates {
+            self.updates.entry(idx).or_insert(value);
+        }
+        let mut button_events = unapplied.button_events;
+        button_events.append(&mut self.button_events);
+        self.button_events = button_events;
+    }
+
+    /// Queues a press or release, so a press is not lost if it is released before the next update.
+    pub fn push_button(&mut self, idx: i32, pressed: bool) {
+        self.button_events.push(ButtonEvent {
+            index: idx,
+            pressed,
+        });
+        self.state.insert(idx, if pressed { 1.0 } else { 0.0 });
+    }
 }
 
+/// The main thread side of a [`DspHandle<D>`], parameters are set with
+/// [`TypedParamIndex<D>`] or by path.
 #[derive(Debug)]
-pub struct StateHandle {
+pub struct StateHandle<D: ?Sized> {
     name: String,
     pub state: State,
+    /// The values of the last state received from the dsp, changes are reported against them.
+    dsp_state: HashMap<i32, f32>,
     meta: HashMap<String, String>,
+    layout: LayoutTree<f32>,
     params: HashMap<i32, Node>,
     params_by_path: BTreeMap<String, i32>,
+    params_by_address: AddressMap,
+    value_mode: ValueMode,
+    subscribers: Vec<Subscriber>,
+    meters: HashMap<i32, Meter>,
+    meter_config: MeterConfig,
     main_rx: Consumer<State>,
     main_tx: Producer<State>,
+    meter_rx: Consumer<MeterSample>,
+    dsp: PhantomData<fn() -> D>,
 }
 
-impl StateHandle {
-    pub fn set_param(&mut self, idx: i32, value: f32) {
-        self.state.insert(idx, value);
-    }
-
-    pub fn get_param(&self, idx: i32) -> Option<&f32> {
-        self.state.state.get(&idx)
+impl<D: ?Sized> StateHandle<D> {
+    /// Sets the parameter with the next [`update`](Self::update) and returns the value set.
+    ///
+    /// NaN, infinite values and bargraphs are refused. The value is checked against the
+    /// declared range and quantized to the step as selected by the [`ValueMode`],
+    /// [`ValueMode::Raw`] passes it on unchanged by default.
+    /// Buttons are pressed by values other than 0 and released by 0,
+    /// every press and release reaches the dsp.
+    ///
+    /// # Errors
+    /// If the dsp has no parameter with this index, it is a bargraph, the value is not finite
+    /// or the value mode rejects it.
+    pub fn set_param(&mut self, idx: TypedParamIndex<D>, value: f32) -> Result<f32, ParamError> {
+        self.set_index(idx.index().0, value)
     }
 
-    pub fn set_by_path(&mut self, path: &str, value: f32) -> Result<(), String> {
-        let idx = if let Some(idx) = self.params_by_path.get(path) {
-            Some(*idx)
-        } else {
-            return Err("No such path".into());
+    /// [`set_param`](Self::set_param) with an index found by path.
+    pub(crate) fn set_index(&mut self, idx: i32, value: f32) -> Result<f32, ParamError> {
+        let Some(node) = self.params.get(&idx) else {
+            return Err(InvalidParamIndex(ParamIndex(idx)).into());
         };
-        if let Some(idx) = idx {
-            self.set_param(idx, value);
+        let value = validate::validate(&node.typ, idx, value, self.value_mode)?;
+        if matches!(node.typ, WidgetType::Button) {
+            self.state.push_button(idx, value != 0.0);
+        } else {
+            self.state.insert(idx, value);
         }
+        Ok(value)
+    }
+
+    /// Presses and releases a button, the dsp sees it pressed for at least one block.
+    ///
+    /// # Errors
+    /// If the dsp has no parameter with this index.
+    pub fn trigger(&mut self, idx: TypedParamIndex<D>) -> Result<(), ParamError> {
+        self.set_param(idx, 1.0)?;
+        self.set_param(idx, 0.0)?;
         Ok(())
     }
 
-    pub fn get_by_path(&self, path: &str) -> Option<&f32> {
+    #[must_use]
+    pub fn value_mode(&self) -> ValueMode {
+        self.value_mode
+    }
+
+    /// Selects how [`set_param`](Self::set_param) validates values.
+    pub fn set_value_mode(&mut self, mode: ValueMode) {
+        self.value_mode = mode;
+    }
+
+    /// Sets the parameter to the value at the position `normalized` in `0..=1` of its range,
+    /// see [`Node::range`].
+    ///
+    /// # Errors
+    /// See [`set_param`](Self::set_param).
+    pub fn set_normalized(
+        &mut self,
+        idx: TypedParamIndex<D>,
+        normalized: f32,
+    ) -> Result<f32, ParamError> {
+        let index = idx.index();
+        let range = self.param_range(index.0).ok_or(InvalidParamIndex(index))?;
+        #[allow(clippy::cast_possible_truncation)]
+        let value = range.from_normalized(f64::from(normalized)) as f32;
+        self.set_param(idx, value)
+    }
+
+    /// The value of the parameter as position in `0..=1` of its range.
+    #[must_use]
+    pub fn get_normalized(&self, idx: i32) -> Option<f32> {
+        let range = self.param_range(idx)?;
+        let value = self.get_param(idx)?;
+        #[allow(clippy::cast_possible_truncation)]
+        let normalized = range.to_normalized(f64::from(*value)) as f32;
+        Some(normalized)
+    }
+
+    /// `value` formatted with the decimals of the step and the unit of the parameter.
+    #[must_use]
+    pub fn value_to_text(&self, idx: i32, value: f32) -> Option<String> {
+        let node = self.params.get(&idx)?;
+        Some(node.range()?.to_text(f64::from(value), node.unit()))
+    }
+
+    /// Parses a value of the parameter like `"-6.0 dB"`, clamped and quantized to its range.
+    #[must_use]
+    pub fn text_to_value(&self, idx: i32, text: &str) -> Option<f32> {
+        let node = self.params.get(&idx)?;
+        #[allow(clippy::cast_possible_truncation)]
+        node.range()?
+            .from_text(text, node.unit())
+            .map(|value| value as f32)
+    }
+
+    fn param_range(&self, idx: i32) -> Option<ParamRange> {
+        self.params.get(&idx).and_then(Node::range)
+    }
+
+    pub fn get_param(&self, idx: i32) -> Option<&f32> {
+        self.state.state.get(&idx)
+    }
+
+    /// The index of the parameter with this path, faust address, shortname
+    /// or the end of a single address like `group/label`.
+    ///
+    /// Of parameters with the same address the first declared one is found.
+    #[must_use]
+    pub fn index_of(&self, path: &str) -> Option<i32> {
         self.params_by_path
             .get(path)
-            .and_then(|idx| self.get_param(*idx))
+            .copied()
+            .or_else(|| self.params_by_address.get(path))
+    }
+
+    /// Sets the parameter found by [`index_of`](Self::index_of).
+    pub fn set_by_path(&mut self, path: &str, value: f32) -> Result<f32, ParamError> {
+        let idx = self
+            .index_of(path)
+            .ok_or_else(|| ParamError::NoSuchPath(path.to_owned()))?;
+        self.set_index(idx, value)
+    }
+
+    /// The parameter found by [`index_of`](Self::index_of).
+    pub fn get_by_path(&self, path: &str) -> Option<&f32> {
+        self.index_of(path).and_then(|idx| self.get_param(idx))
     }
 
     pub fn send(&mut self) {
         self.update();
     }
 
+    /// Exchanges the state with the dsp and reports the values it changed to the subscribers.
     pub fn update(&mut self) {
+        self.receive_meters();
         if let Ok(state) = self.main_rx.pop() {
-            self.state.state = state.state;
+            if state.generation != self.state.generation {
+                // Sent before a swap, the values were carried over by `rebind`.
+            } else if state.applied {
+                let old = std::mem::replace(&mut self.dsp_state, state.state);
+                for (idx, value) in &self.dsp_state {
+                    // Values set since the state was sent are still on their way.
+                    let pending = self.state.updates.contains_key(idx)
+                        || self.state.button_events.iter().any(|e| e.index == *idx);
+                    if !pending {
+                        self.state.state.insert(*idx, *value);
+                    }
+                }
+                self.notify(&old);
+            } else {
+                self.state.requeue(state);
+            }
         }
         if !self.main_tx.is_full() {
             let state = self.state.clone();
@@ -268,7 +584,107 @@ impl StateHandle {
                 eprintln!("error sending state update: {e}");
             } else {
                 self.state.updates.clear();
+                self.state.button_events.clear();
+            }
+        }
+    }
+
+    fn receive_meters(&mut self) {
+        while let Ok(sample) = self.meter_rx.pop() {
+            if sample.generation != self.state.generation {
+                continue;
             }
+            if let Some(meter) = self.meters.get_mut(&sample.index) {
+                meter.push(sample.value);
+            }
+        }
+    }
+
+    /// The values of a bargraph after every block computed by
+    /// [`DspHandle::update_and_compute`], received with [`update`](Self::update).
+    #[must_use]
+    pub fn meter(&self, idx: i32) -> Option<&Meter> {
+        self.meters.get(&idx)
+    }
+
+    /// The [`meter`](Self::meter) of the bargraph found by [`index_of`](Self::index_of).
+    #[must_use]
+    pub fn meter_by_path(&self, path: &str) -> Option<&Meter> {
+        self.index_of(path).and_then(|idx| self.meter(idx))
+    }
+
+    /// All meters by index, for example to reset their peaks.
+    pub fn meters_mut(&mut self) -> impl Iterator<Item = (i32, &mut Meter)> {
+        self.meters.iter_mut().map(|(idx, meter)| (*idx, meter))
+    }
+
+    /// Applies `config` to all meters, also to those of a dsp swapped in later.
+    pub fn set_meter_config(&mut self, config: MeterConfig) {
+        self.meter_config = config;
+        for meter in self.meters.values_mut() {
+            meter.set_config(config);
+        }
+    }
+
+    /// Receives the changes matching `filter` from every [`update`](Self::update).
+    ///
+    /// ```
+    /// # use faust_state::{ChangeFilter, StateHandle};
+    /// # fn run<D: ?Sized>(state: &mut StateHandle<D>, redraw: impl Fn(&str, f32)) {
+    /// let meters = state.subscribe(ChangeFilter::passive());
+    /// state.update();
+    /// for change in meters.try_iter() {
+    ///     redraw(&change.path, change.new);
+    /// }
+    /// # }
+    /// ```
+    pub fn subscribe(&mut self, filter: ChangeFilter) -> Receiver<Change> {
+        let (tx, rx) = mpsc::channel();
+        self.subscribers.push(Subscriber::channel(filter, tx));
+        rx
+    }
+
+    /// Calls `callback` with the changes matching `filter` from every [`update`](Self::update).
+    pub fn on_change(
+        &mut self,
+        filter: ChangeFilter,
+        callback: impl FnMut(&Change) + Send + 'static,
+    ) {
+        self.subscribers
+            .push(Subscriber::callback(filter, Box::new(callback)));
+    }
+
+    /// [`subscribe`](Self::subscribe) as a `Stream`, which ends when the handle is dropped.
+    #[cfg(feature = "stream")]
+    pub fn subscribe_stream(&mut self, filter: ChangeFilter) -> ChangeStream {
+        let (subscriber, stream) = Subscriber::stream(filter);
+        self.subscribers.push(subscriber);
+        stream
+    }
+
+    /// Reports the values of the dsp differing from `old`, in the order of the paths.
+    fn notify(&mut self, old: &HashMap<i32, f32>) {
+        if self.subscribers.is_empty() {
+            return;
+        }
+        for (path, idx) in &self.params_by_path {
+            let Some(&new) = self.dsp_state.get(idx) else {
+                continue;
+            };
+            let old = old.get(idx).copied();
+            if old.map(f32::to_bits) == Some(new.to_bits()) {
+                continue;
+            }
+            let node = &self.params[idx];
+            let change = Change {
+                index: *idx,
+                path: path.clone(),
+                old,
+                new,
+            };
+            self.subscribers.retain_mut(|subscriber| {
+                !subscriber.matches(path, node) || subscriber.notify(&change)
+            });
         }
     }
 
@@ -276,10 +692,20 @@ impl StateHandle {
         &self.params
     }
 
+    /// The parameters by path, the faust address of the widget in the [`layout`](Self::layout)
+    /// with the dsp name as root, see [`Node::address`].
+    ///
+    /// Of parameters with the same address the first declared one is listed.
     pub fn params_by_path(&self) -> impl Iterator<Item = (&String, Option<&f32>)> {
-        self.params_by_path
+        self.params_by_address
             .iter()
-            .map(move |(path, idx)| (path, self.get_param(*idx)))
+            .map(move |(address, idx)| (address, self.get_param(idx)))
+    }
+
+    /// The groups and widgets of the dsp in declaration order.
+    #[must_use]
+    pub fn layout(&self) -> &LayoutTree<f32> {
+        &self.layout
     }
 
     pub fn meta(&self) -> &HashMap<String, String> {
@@ -291,12 +717,51 @@ impl StateHandle {
     }
 }
 
+/// The index of every path, of parameters with the same path the first declared one.
+fn paths_from_layout(
+    layout: &LayoutTree<f32>,
+    params: &HashMap<i32, Node>,
+) -> BTreeMap<String, i32> {
+    let mut paths = BTreeMap::new();
+    for widget in layout.widgets() {
+        if let Some(node) = params.get(&widget.index.0) {
+            paths.entry(node.path()).or_insert(widget.index.0);
+        }
+    }
+    paths
+}
+
+/// The indices of the bargraphs in declaration order.
+fn passives_from_layout(layout: &LayoutTree<f32>, params: &HashMap<i32, Node>) -> Box<[i32]> {
+    layout
+        .widgets()
+        .into_iter()
+        .map(|widget| widget.index.0)
+        .filter(|idx| params.get(idx).is_some_and(|node| node.typ.is_passive()))
+        .collect()
+}
+
+fn meters_from_passives(passives: &[i32], config: MeterConfig) -> HashMap<i32, Meter> {
+    passives
+        .iter()
+        .map(|idx| (*idx, Meter::new(config)))
+        .collect()
+}
+
+fn addresses_from_layout(layout: &LayoutTree<f32>, params: &HashMap<i32, Node>) -> AddressMap {
+    AddressMap::new(layout.widgets().into_iter().filter_map(|widget| {
+        params
+            .get_key_value(&widget.index.0)
+            .map(|(idx, node)| (*idx, node))
+    }))
+}
+
 struct MetaBuilder {
     inner: HashMap<String, String>,
 }
 
 impl MetaBuilder {
-    fn from_dsp<T>(dsp: &impl FaustDsp<T = T>) -> HashMap<String, String> {
+    fn from_dsp<T>(dsp: &(impl DynFaustDsp<T> + ?Sized)) -> HashMap<String, String> {
         let mut metadata = Self {
             inner: HashMap::new(),
         };
@@ -311,17 +776,12 @@ impl faust_types::Meta for MetaBuilder {
     }
 }
 
-#[derive(Debug)]
-struct ParamsBuilder {
-    inner: HashMap<i32, Node>,
-    prefix: Vec<String>,
-    first_group: bool,
-}
-
 #[derive(Debug, Clone, Default)]
 pub struct Node {
     label: String,
     prefix: String,
+    address: String,
+    shortname: String,
     typ: WidgetType,
     metadata: Vec<[String; 2]>,
 }
@@ -337,10 +797,72 @@ impl Node {
         path
     }
 
+    /// The address faust uses in the JSON description and for OSC, like `/volumecontrol/volume`.
+    ///
+    /// The labels of all groups and the widget joined by `/`, starting with `/`,
+    /// so the outermost group, named after the dsp, is the root.
+    /// Spaces and the characters `#*,?[]{}()` are replaced by `_`.
+    /// Widgets with the same labels in the same group share an address.
+    #[must_use]
+    pub fn address(&self) -> &str {
+        &self.address
+    }
+
+    /// The shortest unique end of the address with `/` replaced by `_`, like faust's `shortname`.
+    #[must_use]
+    pub fn shortname(&self) -> &str {
+        &self.shortname
+    }
+
     #[must_use]
     pub fn widget_type(&self) -> &WidgetType {
         &self.typ
     }
+
+    /// Metadata declared for the widget as `[key, value]`.
+    #[must_use]
+    pub fn metadata(&self) -> &[[String; 2]] {
+        &self.metadata
+    }
+
+    /// The value of the first metadata with this key.
+    #[must_use]
+    pub fn metadata_value(&self, key: &str) -> Option<&str> {
+        self.metadata
+            .iter()
+            .find(|[k, _]| k == key)
+            .map(|[_, value]| value.as_str())
+    }
+
+    /// The `unit` metadata, empty without unit.
+    #[must_use]
+    pub fn unit(&self) -> &str {
+        self.metadata_value("unit").unwrap_or_default()
+    }
+
+    /// Range, step and `scale` metadata of the widget, buttons and checkboxes range from 0 to 1.
+    #[must_use]
+    pub fn range(&self) -> Option<ParamRange> {
+        let scale = self
+            .metadata_value("scale")
+            .map_or(Scale::Linear, Scale::from_metadata);
+        let (range, step) = match &self.typ {
+            WidgetType::Unknown => return None,
+            WidgetType::Button | WidgetType::Toggle => (0.0..=1.0, 1.0),
+            WidgetType::VerticalSlider(input)
+            | WidgetType::HorizontalSlider(input)
+            | WidgetType::NumEntry(input) => (input.range.clone(), input.step),
+            WidgetType::HorizontalBarGraph(output) | WidgetType::VerticalBargraph(output) => {
+                (output.range.clone(), 0.0)
+            }
+        };
+        Some(ParamRange::from_f32(
+            *range.start(),
+            *range.end(),
+            step,
+            scale,
+        ))
+    }
 }
 
 /// General types of widgets declared in the DSP
@@ -367,6 +889,15 @@ pub enum WidgetType {
 }
 
 impl WidgetType {
+    /// Bargraphs, whose value is set by the dsp.
+    #[must_use]
+    pub fn is_passive(&self) -> bool {
+        matches!(
+            self,
+            Self::HorizontalBarGraph(_) | Self::VerticalBargraph(_)
+        )
+    }
+
     /// Retrieve the init value for this widget
     #[must_use]
     pub fn init_value(&self) -> f32 {
@@ -387,10 +918,10 @@ pub struct RangedInput {
     /// Initial value defined in the DSP
     pub init: f32,
     /// Available range defined in the DSP
-    /// This range is declared but not enforced
+    /// Enforced by [`StateHandle::set_param`] unless the [`ValueMode`] is raw
     pub range: RangeInclusive<f32>,
     /// Precision of the value
-    /// This value is declared but not enforced
+    /// Enforced by [`StateHandle::set_param`] unless the [`ValueMode`] is raw
     pub step: f32,
 }
 
@@ -403,6 +934,20 @@ impl RangedInput {
             step,
         }
     }
+
+    /// The value nearest to `value` on the steps from the minimum, within the range.
+    #[must_use]
+    pub fn quantize(&self, value: f32) -> f32 {
+        let range = ParamRange::from_f32(
+            *self.range.start(),
+            *self.range.end(),
+            self.step,
+            Scale::Linear,
+        );
+        #[allow(clippy::cast_possible_truncation)]
+        let quantized = range.quantize(f64::from(value)) as f32;
+        quantized
+    }
 }
 
 /// A ranged output value controlled by the DSP.
@@ -420,141 +965,67 @@ impl RangedOutput {
     }
 }
 
-impl ParamsBuilder {
-    fn new() -> Self {
-        Self {
-            inner: HashMap::new(),
-            first_group: true,
-            prefix: Vec::new(),
-            // state: Vec::new(),
-        }
-    }
-    fn from_dsp(dsp: &impl FaustDsp<T = f32>) -> HashMap<i32, Node> {
-        let mut builder = Self::new();
-        dsp.build_user_interface(&mut builder);
-        builder.inner
-    }
-
-    fn open_group(&mut self, label: &str) {
-        if self.first_group {
-            self.first_group = false;
-        } else {
-            self.prefix.push(label.into());
-        }
-    }
-    fn close_group(&mut self) {
-        self.prefix.pop();
-    }
+/// Builds the parameters from the widgets collected by faust-types.
+struct ParamsBuilder;
 
-    fn add_or_update_widget(
-        &mut self,
-        label: &str,
-        idx: ParamIndex,
-        typ: WidgetType,
-        metadata: Option<Vec<[String; 2]>>,
-    ) {
-        let prefix = self.prefix[..].join("/");
-        let idx = idx.0;
-        if let std::collections::hash_map::Entry::Vacant(e) = self.inner.entry(idx) {
-            let node = Node {
-                label: label.to_string(),
-                prefix,
-                typ,
-                metadata: metadata.unwrap_or_default(),
-            };
-            e.insert(node);
-        } else {
-            let node = self.inner.get_mut(&idx).expect("ParamIndex not valid");
-            node.label = label.to_string();
-            node.typ = typ;
-            if let Some(mut metadata) = metadata {
-                node.metadata.append(metadata.as_mut());
-            }
+impl ParamsBuilder {
+    fn from_layout(layout: &LayoutTree<f32>) -> HashMap<i32, Node> {
+        let mut nodes: Vec<(i32, Node)> = layout
+            .widgets()
+            .into_iter()
+            .map(|widget| (widget.index.0, Node::from(widget)))
+            .collect();
+        let addresses: Vec<String> = nodes.iter().map(|(_, node)| node.address.clone()).collect();
+        let shortnames = address::shortnames(&addresses);
+        for (_, node) in &mut nodes {
+            node.shortname = shortnames.get(&node.address).cloned().unwrap_or_default();
         }
+        nodes.into_iter().collect()
     }
 }
 
-impl UI<f32> for ParamsBuilder {
-    fn open_tab_box(&mut self, label: &str) {
-        self.open_group(label);
-    }
-    fn open_horizontal_box(&mut self, label: &str) {
-        self.open_group(label);
-    }
-    fn open_vertical_box(&mut self, label: &str) {
-        self.open_group(label);
-    }
-    fn close_box(&mut self) {
-        self.close_group();
-    }
-
-    // -- active widgets
-    fn add_button(&mut self, label: &str, param: ParamIndex) {
-        self.add_or_update_widget(label, param, WidgetType::Button, None);
-    }
-    fn add_check_button(&mut self, label: &str, param: ParamIndex) {
-        self.add_or_update_widget(label, param, WidgetType::Toggle, None);
-    }
-    fn add_vertical_slider(
-        &mut self,
-        label: &str,
-        param: ParamIndex,
-        init: f32,
-        min: f32,
-        max: f32,
-        step: f32,
-    ) {
-        let typ = WidgetType::VerticalSlider(RangedInput::new(init, min, max, step));
-        self.add_or_update_widget(label, param, typ, None);
-    }
-    fn add_horizontal_slider(
-        &mut self,
-        label: &str,
-        param: ParamIndex,
-        init: f32,
-        min: f32,
-        max: f32,
-        step: f32,
-    ) {
-        let typ = WidgetType::HorizontalSlider(RangedInput::new(init, min, max, step));
-        self.add_or_update_widget(label, param, typ, None);
-    }
-    fn add_num_entry(
-        &mut self,
-        label: &str,
-        param: ParamIndex,
-        init: f32,
-        min: f32,
-        max: f32,
-        step: f32,
-    ) {
-        let typ = WidgetType::NumEntry(RangedInput::new(init, min, max, step));
-        self.add_or_update_widget(label, param, typ, None);
-    }
-
-    // -- passive widgets
-    fn add_horizontal_bargraph(&mut self, label: &str, param: ParamIndex, min: f32, max: f32) {
-        let typ = WidgetType::HorizontalBarGraph(RangedOutput::new(min, max));
-        self.add_or_update_widget(label, param, typ, None);
-    }
-    fn add_vertical_bargraph(&mut self, label: &str, param: ParamIndex, min: f32, max: f32) {
-        let typ = WidgetType::VerticalBargraph(RangedOutput::new(min, max));
-        self.add_or_update_widget(label, param, typ, None);
-    }
-
-    // -- metadata declarations
-    fn declare(&mut self, param: Option<ParamIndex>, key: &str, value: &str) {
-        if let Some(param_index) = param {
-            if !self.inner.contains_key(&param_index.0) {
-                self.add_or_update_widget(
-                    "Unknown",
-                    param_index,
-                    WidgetType::default(),
-                    Some(vec![[key.to_string(), value.to_string()]]),
-                );
-            } else if let Some(node) = self.inner.get_mut(&param_index.0) {
-                node.metadata.push([key.to_string(), value.to_string()]);
+impl From<&Widget<f32>> for Node {
+    fn from(widget: &Widget<f32>) -> Self {
+        let typ = match widget.kind {
+            WidgetKind::Button => WidgetType::Button,
+            WidgetKind::CheckButton => WidgetType::Toggle,
+            WidgetKind::VerticalSlider {
+                init,
+                min,
+                max,
+                step,
+            } => WidgetType::VerticalSlider(RangedInput::new(init, min, max, step)),
+            WidgetKind::HorizontalSlider {
+                init,
+                min,
+                max,
+                step,
+            } => WidgetType::HorizontalSlider(RangedInput::new(init, min, max, step)),
+            WidgetKind::NumEntry {
+                init,
+                min,
+                max,
+                step,
+            } => WidgetType::NumEntry(RangedInput::new(init, min, max, step)),
+            WidgetKind::HorizontalBargraph { min, max } => {
+                WidgetType::HorizontalBarGraph(RangedOutput::new(min, max))
             }
+            WidgetKind::VerticalBargraph { min, max } => {
+                WidgetType::VerticalBargraph(RangedOutput::new(min, max))
+            }
+        };
+        Self {
+            label: widget.label.clone(),
+            // The outermost group is the dsp itself and not part of the path.
+            prefix: widget.groups.get(1..).unwrap_or_default().join("/"),
+            address: address::build(&widget.groups, &widget.label),
+            shortname: String::new(),
+            typ,
+            metadata: widget
+                .metadata
+                .iter()
+                .map(|(key, value)| [key.clone(), value.clone()])
+                .collect(),
         }
     }
 }
@@ -0,0 +1,240 @@
+use std::collections::VecDeque;
+
+/// Blocks per bargraph the ring from the audio thread holds until the next
+/// [`StateHandle::update`](crate::StateHandle::update), later values are dropped.
+pub const METER_RING_BLOCKS: usize = 256;
+
+/// The value of a bargraph after a block, sent by the audio thread.
+#[derive(Debug, Clone, Copy)]
+pub struct MeterSample {
+    pub generation: u32,
+    pub index: i32,
+    pub value: f32,
+}
+
+/// History length, peak-hold and decay of the meters, all counted in blocks.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MeterConfig {
+    /// Number of block values kept.
+    pub history: usize,
+    /// Number of blocks the peak is held before it decays.
+    pub hold: usize,
+    /// Amount the peak falls per block once the hold time is over,
+    /// in the unit of the bargraph, for example dB.
+    pub decay: f32,
+}
+
+impl Default for MeterConfig {
+    /// A history of 1024 blocks, without peak-hold the peak follows the value.
+    fn default() -> Self {
+        Self {
+            history: 1024,
+            hold: 0,
+            decay: f32::INFINITY,
+        }
+    }
+}
+
+/// The values of a bargraph after each block, oldest first.
+#[derive(Debug, Clone)]
+pub struct Meter {
+    config: MeterConfig,
+    values: VecDeque<f32>,
+    peak: Option<f32>,
+    held: usize,
+}
+
+impl Meter {
+    #[must_use]
+    pub fn new(config: MeterConfig) -> Self {
+        Self {
+            config,
+            values: VecDeque::with_capacity(config.history),
+            peak: None,
+            held: 0,
+        }
+    }
+
+    pub fn push(&mut self, value: f32) {
+        if self.config.history > 0 {
+            if self.values.len() == self.config.history {
+                self.values.pop_front();
+            }
+            self.values.push_back(value);
+        }
+        match self.peak {
+            Some(peak) if peak > value => {
+                if self.held < self.config.hold {
+                    self.held += 1;
+                } else {
+                    self.peak = Some((peak - self.config.decay).max(value));
+                }
+            }
+            _ => {
+                self.peak = Some(value);
+                self.held = 0;
+            }
+        }
+    }
+
+    #[must_use]
+    pub fn config(&self) -> MeterConfig {
+        self.config
+    }
+
+    /// Keeps the most recent values that fit into the new history length.
+    pub fn set_config(&mut self, config: MeterConfig) {
+        let excess = self.values.len().saturating_sub(config.history);
+        self.values.drain(..excess);
+        self.config = config;
+    }
+
+    /// The value after the last block.
+    #[must_use]
+    pub fn latest(&self) -> Option<f32> {
+        self.values.back().copied()
+    }
+
+    /// All kept values, oldest first.
+    pub fn history(&self) -> impl Iterator<Item = f32> + '_ {
+        self.values.iter().copied()
+    }
+
+    /// The values of the last `blocks` blocks, oldest first.
+    pub fn window(&self, blocks: usize) -> impl Iterator<Item = f32> + '_ {
+        self.values
+            .iter()
+            .skip(self.values.len().saturating_sub(blocks))
+            .copied()
+    }
+
+    #[must_use]
+    pub fn min(&self, blocks: usize) -> Option<f32> {
+        self.window(blocks).reduce(f32::min)
+    }
+
+    #[must_use]
+    pub fn max(&self, blocks: usize) -> Option<f32> {
+        self.window(blocks).reduce(f32::max)
+    }
+
+    /// The root mean square of the last `blocks` values.
+    #[must_use]
+    pub fn rms(&self, blocks: usize) -> Option<f32> {
+        let (count, sum) = self
+            .window(blocks)
+            .fold((0_u32, 0.0_f32), |(count, sum), value| {
+                (count + 1, value.mul_add(value, sum))
+            });
+        #[allow(clippy::cast_precision_loss)]
+        (count > 0).then(|| (sum / count as f32).sqrt())
+    }
+
+    /// The held peak, decaying after [`MeterConfig::hold`] blocks.
+    #[must_use]
+    pub fn peak(&self) -> Option<f32> {
+        self.peak
+    }
+
+    pub fn reset_peak(&mut self) {
+        self.peak = self.latest();
+        self.held = 0;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Meter, MeterConfig};
+    use crate::{
+        test_dsp::{bargraph, slider, TestDsp},
+        DspHandle,
+    };
+
+    fn meter(history: usize, hold: usize, decay: f32, values: &[f32]) -> Meter {
+        let mut meter = Meter::new(MeterConfig {
+            history,
+            hold,
+            decay,
+        });
+        for value in values {
+            meter.push(*value);
+        }
+        meter
+    }
+
+    #[test]
+    fn keeps_the_latest_values() {
+        let meter = meter(3, 0, f32::INFINITY, &[1.0, 2.0, 3.0, 4.0]);
+        assert_eq!(meter.history().collect::<Vec<_>>(), [2.0, 3.0, 4.0]);
+        assert_eq!(meter.latest(), Some(4.0));
+        assert_eq!(meter.window(2).collect::<Vec<_>>(), [3.0, 4.0]);
+        assert_eq!(meter.window(5).count(), 3);
+    }
+
+    #[test]
+    fn computes_statistics_over_the_window() {
+        let meter = meter(8, 0, f32::INFINITY, &[5.0, -3.0, 4.0, 1.0]);
+        assert_eq!(meter.min(3), Some(-3.0));
+        assert_eq!(meter.max(2), Some(4.0));
+        assert_eq!(meter.max(8), Some(5.0));
+        let rms = meter.rms(3).expect("values in the window");
+        assert!((rms - (26.0_f32 / 3.0).sqrt()).abs() < 1e-6);
+        assert_eq!(meter.rms(0), None);
+        assert_eq!(Meter::new(MeterConfig::default()).min(1), None);
+    }
+
+    #[test]
+    fn peak_follows_the_value_by_default() {
+        let meter = meter(0, 0, f32::INFINITY, &[3.0, 1.0]);
+        assert_eq!(meter.peak(), Some(1.0));
+        assert_eq!(meter.history().count(), 0);
+    }
+
+    #[test]
+    fn holds_the_peak_and_decays() {
+        let mut meter = meter(4, 2, 1.5, &[6.0, 0.0, 0.0]);
+        assert_eq!(meter.peak(), Some(6.0));
+        meter.push(0.0);
+        assert_eq!(meter.peak(), Some(4.5));
+        meter.push(0.0);
+        assert_eq!(meter.peak(), Some(3.0));
+        meter.push(3.5);
+        assert_eq!(meter.peak(), Some(3.5));
+        meter.push(0.0);
+        meter.reset_peak();
+        assert_eq!(meter.peak(), Some(0.0));
+    }
+
+    #[test]
+    fn shortens_the_history_to_the_new_config() {
+        let mut meter = meter(4, 0, f32::INFINITY, &[1.0, 2.0, 3.0, 4.0]);
+        let config = MeterConfig {
+            history: 2,
+            ..MeterConfig::default()
+        };
+        meter.set_config(config);
+        assert_eq!(meter.config(), config);
+        assert_eq!(meter.history().collect::<Vec<_>>(), [3.0, 4.0]);
+        meter.push(5.0);
+        assert_eq!(meter.history().collect::<Vec<_>>(), [4.0, 5.0]);
+    }
+
+    #[test]
+    fn receives_a_value_per_block() {
+        let widgets = vec![
+            slider("gain", 0.5, 0.0, 1.0, 0.0),
+            bargraph("level", -70.0, 6.0),
+        ];
+        let (mut dsp, mut state) = DspHandle::from_dsp(Box::new(TestDsp::with(1, 0.0, widgets)));
+        let mut output = [0.0; 4];
+        for level in [-12.0, -6.0, -9.0] {
+            dsp.dsp.level = level;
+            dsp.update_and_compute(4, &[], &mut [&mut output]);
+        }
+        state.update();
+        assert!(state.meter_by_path("gain").is_none());
+        let meter = state.meter_by_path("level").expect("meter of the bargraph");
+        assert_eq!(meter.history().collect::<Vec<_>>(), [-12.0, -6.0, -9.0]);
+        assert_eq!(meter.max(3), Some(-6.0));
+    }
+}
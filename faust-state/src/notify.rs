@@ -0,0 +1,301 @@
+use crate::{Node, WidgetType};
+use std::sync::mpsc::Sender;
+
+/// A parameter changed by the dsp, reported by [`StateHandle::update`](crate::StateHandle::update).
+///
+/// Values set with [`StateHandle::set_param`](crate::StateHandle::set_param) are reported
+/// once the dsp applied them.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Change {
+    pub index: i32,
+    /// See [`Node::path`].
+    pub path: String,
+    /// `None` if the parameter had no value before.
+    pub old: Option<f32>,
+    pub new: f32,
+}
+
+/// Selects the changes passed to a subscriber, all of them by default.
+///
+/// ```
+/// # use faust_state::{ChangeFilter, StateHandle};
+/// # fn run<D: ?Sized>(state: &mut StateHandle<D>) {
+/// let meters = state.subscribe(ChangeFilter::passive().prefix("channel_0/"));
+/// # }
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct ChangeFilter {
+    prefix: Option<String>,
+    widget_type: Option<fn(&WidgetType) -> bool>,
+}
+
+impl ChangeFilter {
+    #[must_use]
+    pub fn all() -> Self {
+        Self::default()
+    }
+
+    /// Changes of bargraphs.
+    #[must_use]
+    pub fn passive() -> Self {
+        Self::all().widget_type(WidgetType::is_passive)
+    }
+
+    /// Changes of buttons, checkboxes, sliders and numeric entries.
+    #[must_use]
+    pub fn active() -> Self {
+        Self::all().widget_type(|typ| !typ.is_passive())
+    }
+
+    /// Parameters whose path or address starts with `prefix`.
+    #[must_use]
+    pub fn prefix(mut self, prefix: impl Into<String>) -> Self {
+        self.prefix = Some(prefix.into());
+        self
+    }
+
+    /// Parameters whose widget type is accepted by `accept`.
+    #[must_use]
+    pub fn widget_type(mut self, accept: fn(&WidgetType) -> bool) -> Self {
+        self.widget_type = Some(accept);
+        self
+    }
+
+    #[must_use]
+    pub fn matches(&self, path: &str, node: &Node) -> bool {
+        let prefix_matches = self.prefix.as_deref().map_or(true, |prefix| {
+            path.starts_with(prefix) || node.address().starts_with(prefix)
+        });
+        prefix_matches
+            && self
+                .widget_type
+                .map_or(true, |accept| accept(node.widget_type()))
+    }
+}
+
+enum Sink {
+    Channel(Sender<Change>),
+    Callback(Box<dyn FnMut(&Change) + Send>),
+    #[cfg(feature = "stream")]
+    Stream(stream::Sender),
+}
+
+pub struct Subscriber {
+    filter: ChangeFilter,
+    sink: Sink,
+}
+
+impl Subscriber {
+    pub fn channel(filter: ChangeFilter, tx: Sender<Change>) -> Self {
+        Self {
+            filter,
+            sink: Sink::Channel(tx),
+        }
+    }
+
+    pub fn callback(filter: ChangeFilter, callback: Box<dyn FnMut(&Change) + Send>) -> Self {
+        Self {
+            filter,
+            sink: Sink::Callback(callback),
+        }
+    }
+
+    #[cfg(feature = "stream")]
+    pub fn stream(filter: ChangeFilter) -> (Self, ChangeStream) {
+        let (tx, rx) = stream::channel();
+        let subscriber = Self {
+            filter,
+            sink: Sink::Stream(tx),
+        };
+        (subscriber, rx)
+    }
+
+    pub fn matches(&self, path: &str, node: &Node) -> bool {
+        self.filter.matches(path, node)
+    }
+
+    /// Passes the change on, returns `false` once the receiving side is gone.
+    pub fn notify(&mut self, change: &Change) -> bool {
+        match &mut self.sink {
+            Sink::Channel(tx) => tx.send(change.clone()).is_ok(),
+            Sink::Callback(callback) => {
+                callback(change);
+                true
+            }
+            #[cfg(feature = "stream")]
+            Sink::Stream(tx) => tx.send(change.clone()),
+        }
+    }
+}
+
+impl std::fmt::Debug for Subscriber {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let sink = match self.sink {
+            Sink::Channel(_) => "Channel",
+            Sink::Callback(_) => "Callback",
+            #[cfg(feature = "stream")]
+            Sink::Stream(_) => "Stream",
+        };
+        f.debug_struct("Subscriber")
+            .field("filter", &self.filter)
+            .field("sink", &sink)
+            .finish()
+    }
+}
+
+#[cfg(feature = "stream")]
+pub use stream::ChangeStream;
+
+#[cfg(feature = "stream")]
+mod stream {
+    use super::Change;
+    use futures_core::Stream;
+    use std::{
+        collections::VecDeque,
+        pin::Pin,
+        sync::{Arc, Mutex, MutexGuard, PoisonError},
+        task::{Context, Poll, Waker},
+    };
+
+    #[derive(Debug, Default)]
+    struct Queue {
+        changes: VecDeque<Change>,
+        waker: Option<Waker>,
+        closed: bool,
+    }
+
+    fn lock(queue: &Mutex<Queue>) -> MutexGuard<'_, Queue> {
+        queue.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    pub fn channel() -> (Sender, ChangeStream) {
+        let queue = Arc::new(Mutex::new(Queue::default()));
+        (
+            Sender {
+                queue: Arc::clone(&queue),
+            },
+            ChangeStream { queue },
+        )
+    }
+
+    pub struct Sender {
+        queue: Arc<Mutex<Queue>>,
+    }
+
+    impl Sender {
+        /// Returns `false` once the stream was dropped.
+        pub fn send(&self, change: Change) -> bool {
+            if Arc::strong_count(&self.queue) == 1 {
+                return false;
+            }
+            let waker = {
+                let mut queue = lock(&self.queue);
+                queue.changes.push_back(change);
+                queue.waker.take()
+            };
+            if let Some(waker) = waker {
+                waker.wake();
+            }
+            true
+        }
+    }
+
+    impl Drop for Sender {
+        fn drop(&mut self) {
+            let waker = {
+                let mut queue = lock(&self.queue);
+                queue.closed = true;
+                queue.waker.take()
+            };
+            if let Some(waker) = waker {
+                waker.wake();
+            }
+        }
+    }
+
+    /// Changes reported by [`StateHandle::update`](crate::StateHandle::update) as a `Stream`.
+    ///
+    /// The stream ends when the `StateHandle` is dropped.
+    #[derive(Debug)]
+    pub struct ChangeStream {
+        queue: Arc<Mutex<Queue>>,
+    }
+
+    impl Stream for ChangeStream {
+        type Item = Change;
+
+        fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Change>> {
+            let mut queue = lock(&self.queue);
+            match queue.changes.pop_front() {
+                Some(change) => Poll::Ready(Some(change)),
+                None if queue.closed => Poll::Ready(None),
+                None => {
+                    queue.waker = Some(cx.waker().clone());
+                    Poll::Pending
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Change, ChangeFilter};
+    use crate::{
+        test_dsp::{param, slider, TestDsp},
+        DspHandle, StateHandle,
+    };
+    use std::sync::mpsc::Receiver;
+
+    fn gain() -> (DspHandle<TestDsp>, StateHandle<TestDsp>, Receiver<Change>) {
+        let dsp = TestDsp::with(1, 0.0, vec![slider("gain", 0.5, 0.0, 1.0, 0.0)]);
+        let (dsp, mut state) = DspHandle::from_dsp(Box::new(dsp));
+        let changes = state.subscribe(ChangeFilter::all());
+        (dsp, state, changes)
+    }
+
+    fn compute(dsp: &mut DspHandle<TestDsp>) {
+        dsp.update_and_compute(1, &[], &mut [&mut [0.0]]);
+    }
+
+    fn change(old: f32, new: f32) -> Change {
+        Change {
+            index: 0,
+            path: "gain".into(),
+            old: Some(old),
+            new,
+        }
+    }
+
+    #[test]
+    fn reports_values_applied_by_the_dsp() {
+        let (mut dsp, mut state, changes) = gain();
+        state.set_param(param(0), 0.25).expect("valid value");
+        state.update();
+        assert_eq!(changes.try_iter().count(), 0);
+        compute(&mut dsp);
+        state.update();
+        assert_eq!(changes.try_iter().collect::<Vec<_>>(), [change(0.5, 0.25)]);
+        compute(&mut dsp);
+        state.update();
+        assert_eq!(changes.try_iter().count(), 0);
+    }
+
+    #[test]
+    fn ignores_values_set_since_the_last_update() {
+        let (mut dsp, mut state, changes) = gain();
+        state.set_param(param(0), 0.25).expect("valid value");
+        state.update();
+        compute(&mut dsp);
+        state.set_param(param(0), 0.75).expect("valid value");
+        state.update();
+        assert_eq!(changes.try_iter().collect::<Vec<_>>(), [change(0.5, 0.25)]);
+        assert_eq!(state.get_param(0), Some(&0.75));
+        compute(&mut dsp);
+        state.update();
+        assert_eq!(changes.try_iter().collect::<Vec<_>>(), [change(0.25, 0.75)]);
+        compute(&mut dsp);
+        state.update();
+        assert_eq!(changes.try_iter().count(), 0);
+    }
+}
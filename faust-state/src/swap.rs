@@ -0,0 +1,359 @@
+use crate::{
+    addresses_from_layout, meters_from_passives, passives_from_layout, paths_from_layout,
+    DspHandle, MetaBuilder, Node, ParamsBuilder, State, StateHandle,
+};
+use faust_types::{collect::LayoutTree, dynamic::DynFaustDsp, ParamIndex};
+use rtrb::{Consumer, Producer, RingBuffer};
+use std::{
+    collections::{BTreeMap, HashMap},
+    sync::{
+        atomic::{AtomicI32, Ordering},
+        Arc,
+    },
+};
+
+/// A dsp on its way into or out of the audio thread.
+/// It travels back after it was replaced, so nothing is freed on the audio thread.
+struct Swap<T: ?Sized> {
+    dsp: Box<T>,
+    /// Pairs of (old, new) param indices with the same path.
+    carry_over: Vec<(i32, i32)>,
+    /// Indices of the bargraphs of the dsp.
+    passives: Box<[i32]>,
+    generation: u32,
+}
+
+/// Audio thread side of the hot swap.
+pub struct HotSwap<T: ?Sized> {
+    swap_rx: Consumer<Swap<T>>,
+    retired_tx: Producer<Swap<T>>,
+    /// The replaced dsp while it is faded out.
+    fading: Option<Swap<T>>,
+    /// A replaced dsp that could not be sent back yet.
+    retired: Option<Swap<T>>,
+    fade_pos: usize,
+    fade_len: usize,
+    scratch: Vec<f32>,
+    max_block_size: usize,
+    /// Sample rate of the running dsp, new dsps are initialized with it by the swapper.
+    sample_rate: Arc<AtomicI32>,
+}
+
+impl<T: ?Sized> std::fmt::Debug for HotSwap<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("HotSwap")
+            .field("fading", &self.fading.is_some())
+            .field("fade_pos", &self.fade_pos)
+            .field("fade_len", &self.fade_len)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<T: ?Sized> HotSwap<T> {
+    pub fn set_sample_rate(&self, sample_rate: i32) {
+        self.sample_rate.store(sample_rate, Ordering::Relaxed);
+    }
+}
+
+impl<T> HotSwap<T>
+where
+    T: DynFaustDsp<f32> + ?Sized,
+{
+    pub fn compute(
+        &mut self,
+        dsp: &mut Box<T>,
+        generation: &mut u32,
+        passives: &mut Box<[i32]>,
+        count: i32,
+        inputs: &[&[f32]],
+        outputs: &mut [&mut [f32]],
+    ) {
+        if let Some(retired) = self.retired.take() {
+            if let Err(rtrb::PushError::Full(retired)) = self.retired_tx.push(retired) {
+                self.retired = Some(retired);
+            }
+        }
+        if self.fading.is_none() && self.retired.is_none() {
+            if let Ok(mut swap) = self.swap_rx.pop() {
+                for (old, new) in &swap.carry_over {
+                    if let Some(value) = dsp.get_param(ParamIndex(*old)) {
+                        swap.dsp.set_param(ParamIndex(*new), value);
+                    }
+                }
+                std::mem::swap(dsp, &mut swap.dsp);
+                std::mem::swap(passives, &mut swap.passives);
+                *generation = swap.generation;
+                self.fading = Some(swap);
+                self.fade_pos = 0;
+            }
+        }
+
+        let Some(fading) = self.fading.as_mut() else {
+            dsp.compute(count, inputs, outputs);
+            return;
+        };
+        let len = count as usize;
+        let channels = outputs.len();
+        if len > self.max_block_size || self.scratch.len() < len * channels {
+            // Block too large for the preallocated scratch buffer: switch without crossfade.
+            self.fade_pos = self.fade_len;
+        } else {
+            fading.dsp.compute(count, inputs, outputs);
+            for (channel, output) in outputs.iter().enumerate() {
+                self.scratch[channel * len..(channel + 1) * len].copy_from_slice(&output[..len]);
+            }
+        }
+        dsp.compute(count, inputs, outputs);
+        if self.fade_pos < self.fade_len {
+            #[allow(clippy::cast_precision_loss)]
+            for (channel, output) in outputs.iter_mut().enumerate() {
+                let old = &self.scratch[channel * len..(channel + 1) * len];
+                for (i, (new, old)) in output[..len].iter_mut().zip(old).enumerate() {
+                    let gain = ((self.fade_pos + i) as f32 / self.fade_len as f32).min(1.0);
+                    *new = (*new).mul_add(gain, *old * (1.0 - gain));
+                }
+            }
+            self.fade_pos += len;
+        }
+        if self.fade_pos >= self.fade_len {
+            self.retired = self.fading.take();
+        }
+    }
+}
+
+/// Replaces the dsp of a running [`DspHandle`] from another thread.
+///
+/// The new dsp is initialized with the sample rate of the running one before it is sent,
+/// parameter values are carried over by path and the output is crossfaded.
+/// Replaced dsps are sent back and dropped by the swapper.
+pub struct DspSwapper<T: ?Sized> {
+    swap_tx: Producer<Swap<T>>,
+    retired_rx: Consumer<Swap<T>>,
+    params_by_path: BTreeMap<String, i32>,
+    generation: u32,
+    sample_rate: Arc<AtomicI32>,
+    /// Channels of the running dsp, the buffers of the audio thread are sized for them.
+    inputs: i32,
+    outputs: i32,
+}
+
+impl<T: ?Sized> std::fmt::Debug for DspSwapper<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("DspSwapper")
+            .field("params_by_path", &self.params_by_path)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<T> DspHandle<T>
+where
+    T: DynFaustDsp<f32> + ?Sized + 'static,
+{
+    /// Enables swapping the dsp while it is running.
+    ///
+    /// Must be called before the handle is moved to the audio thread.
+    /// `max_block_size` is the largest `count` passed to `update_and_compute`
+    /// that is still crossfaded, `crossfade` the length of the crossfade in samples.
+    pub fn hot_swap(&mut self, max_block_size: usize, crossfade: usize) -> DspSwapper<T> {
+        let (swap_tx, swap_rx) = RingBuffer::new(1).split();
+        let (retired_tx, retired_rx) = RingBuffer::new(1).split();
+        let sample_rate = Arc::new(AtomicI32::new(self.dsp.get_sample_rate()));
+        self.hot_swap = Some(HotSwap {
+            swap_rx,
+            retired_tx,
+            fading: None,
+            retired: None,
+            fade_pos: 0,
+            fade_len: crossfade,
+            scratch: vec![0.0; max_block_size * self.num_outputs()],
+            max_block_size,
+            sample_rate: Arc::clone(&sample_rate),
+        });
+        let layout = LayoutTree::from_dsp(&*self.dsp);
+        DspSwapper {
+            swap_tx,
+            retired_rx,
+            params_by_path: paths_from_layout(&layout, &ParamsBuilder::from_layout(&layout)),
+            generation: self.generation,
+            sample_rate,
+            inputs: self.dsp.get_num_inputs(),
+            outputs: self.dsp.get_num_outputs(),
+        }
+    }
+}
+
+impl<T> DspSwapper<T>
+where
+    T: DynFaustDsp<f32> + ?Sized,
+{
+    /// Sends a new dsp to the audio thread and rebinds `state` to its parameters.
+    ///
+    /// The dsp is initialized with the sample rate of the running dsp here,
+    /// the audio thread only carries over the parameter values.
+    ///
+    /// Returns the dsp if the previous swap is still in progress or if its number of inputs
+    /// or outputs differs from the running dsp.
+    pub fn swap(&mut self, mut dsp: Box<T>, state: &mut StateHandle<T>) -> Result<(), Box<T>> {
+        self.collect_retired();
+        if dsp.get_num_inputs() != self.inputs || dsp.get_num_outputs() != self.outputs {
+            return Err(dsp);
+        }
+        if self.swap_tx.is_full() {
+            return Err(dsp);
+        }
+        dsp.init(self.sample_rate.load(Ordering::Relaxed));
+        let meta = MetaBuilder::from_dsp(&*dsp);
+        let layout = LayoutTree::from_dsp(&*dsp);
+        let params = ParamsBuilder::from_layout(&layout);
+        let params_by_path = paths_from_layout(&layout, &params);
+        let carry_over = params_by_path
+            .iter()
+            .filter_map(|(path, new)| self.params_by_path.get(path).map(|old| (*old, *new)))
+            .collect();
+        let generation = self.generation.wrapping_add(1);
+        let swap = Swap {
+            dsp,
+            carry_over,
+            passives: passives_from_layout(&layout, &params),
+            generation,
+        };
+        if let Err(rtrb::PushError::Full(swap)) = self.swap_tx.push(swap) {
+            return Err(swap.dsp);
+        }
+        state.rebind(meta, layout, params, generation);
+        self.params_by_path = params_by_path;
+        self.generation = generation;
+        Ok(())
+    }
+
+    /// Drops dsps that were replaced on the audio thread.
+    pub fn collect_retired(&mut self) {
+        while self.retired_rx.pop().is_ok() {}
+    }
+}
+
+impl<D: ?Sized> StateHandle<D> {
+    /// Switches to the params of a new dsp, keeping values of params with the same path.
+    fn rebind(
+        &mut self,
+        meta: HashMap<String, String>,
+        layout: LayoutTree<f32>,
+        params: HashMap<i32, Node>,
+        generation: u32,
+    ) {
+        let params_by_path = paths_from_layout(&layout, &params);
+        let params_by_address = addresses_from_layout(&layout, &params);
+        let mut state = State {
+            updates: HashMap::with_capacity(params.len()),
+            state: HashMap::with_capacity(params.len()),
+            button_events: Vec::new(),
+            generation,
+            applied: false,
+        };
+        for (path, idx) in &params_by_path {
+            let value = self
+                .get_by_path(path)
+                .copied()
+                .unwrap_or_else(|| params[idx].widget_type().init_value());
+            state.insert(*idx, value);
+        }
+        self.dsp_state.clone_from(&state.state);
+        self.state = state;
+        self.meta = meta;
+        self.layout = layout;
+        self.params = params;
+        self.params_by_path = params_by_path;
+        self.params_by_address = params_by_address;
+        self.meters = meters_from_passives(
+            &passives_from_layout(&self.layout, &self.params),
+            self.meter_config,
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        test_dsp::{slider, TestDsp},
+        DspHandle, StateHandle,
+    };
+    use faust_types::FaustDsp;
+
+    fn gain(level: f32, outputs: i32) -> TestDsp {
+        TestDsp::with(outputs, level, vec![slider("gain", 0.5, 0.0, 1.0, 0.0)])
+    }
+
+    fn run(dsp: &mut DspHandle<TestDsp>, state: &mut StateHandle<TestDsp>, count: i32) -> Vec<f32> {
+        let mut output = vec![0.0; count as usize];
+        state.update();
+        dsp.update_and_compute(count, &[], &mut [&mut output]);
+        output
+    }
+
+    #[test]
+    fn crossfades_to_the_new_dsp() {
+        let (mut dsp, mut state) = DspHandle::from_dsp(Box::new(gain(0.0, 1)));
+        let mut swapper = dsp.hot_swap(4, 4);
+        assert!(swapper.swap(Box::new(gain(1.0, 1)), &mut state).is_ok());
+        assert_eq!(run(&mut dsp, &mut state, 2), [0.0, 0.25]);
+        assert_eq!(run(&mut dsp, &mut state, 2), [0.5, 0.75]);
+        assert_eq!(run(&mut dsp, &mut state, 2), [1.0, 1.0]);
+    }
+
+    #[test]
+    fn switches_blocks_too_large_for_the_crossfade() {
+        let (mut dsp, mut state) = DspHandle::from_dsp(Box::new(gain(0.0, 1)));
+        let mut swapper = dsp.hot_swap(2, 4);
+        assert!(swapper.swap(Box::new(gain(1.0, 1)), &mut state).is_ok());
+        assert_eq!(run(&mut dsp, &mut state, 3), [1.0; 3]);
+    }
+
+    #[test]
+    fn rejects_dsps_with_other_channels() {
+        let (mut dsp, mut state) = DspHandle::from_dsp(Box::new(gain(0.0, 1)));
+        let mut swapper = dsp.hot_swap(4, 0);
+        assert!(swapper.swap(Box::new(gain(1.0, 2)), &mut state).is_err());
+        assert!(swapper.swap(Box::new(gain(1.0, 1)), &mut state).is_ok());
+        assert!(swapper.swap(Box::new(gain(1.0, 1)), &mut state).is_err());
+        run(&mut dsp, &mut state, 1);
+        swapper.collect_retired();
+        assert!(swapper.swap(Box::new(gain(1.0, 1)), &mut state).is_ok());
+    }
+
+    #[test]
+    fn initializes_with_the_sample_rate_of_the_running_dsp() {
+        let (mut dsp, mut state) = DspHandle::from_dsp(Box::new(gain(0.0, 1)));
+        let mut swapper = dsp.hot_swap(4, 0);
+        dsp.init(44100);
+        assert!(swapper.swap(Box::new(gain(1.0, 1)), &mut state).is_ok());
+        run(&mut dsp, &mut state, 1);
+        assert_eq!(dsp.dsp.get_sample_rate(), 44100);
+    }
+
+    #[test]
+    fn carries_over_values() {
+        let (mut dsp, mut state) = DspHandle::from_dsp(Box::new(gain(0.0, 1)));
+        let mut swapper = dsp.hot_swap(4, 0);
+        state.set_by_path("gain", 0.25).expect("valid value");
+        run(&mut dsp, &mut state, 1);
+        assert!(swapper.swap(Box::new(gain(1.0, 1)), &mut state).is_ok());
+        assert_eq!(state.get_by_path("gain"), Some(&0.25));
+        assert_eq!(run(&mut dsp, &mut state, 1), [1.0]);
+        assert_eq!(dsp.dsp.values(), [0.25]);
+    }
+
+    #[test]
+    fn keeps_updates_until_the_swapped_dsp_applied_them() {
+        let (mut dsp, mut state) = DspHandle::from_dsp(Box::new(gain(0.0, 1)));
+        let mut swapper = dsp.hot_swap(4, 0);
+        assert!(swapper.swap(Box::new(gain(1.0, 1)), &mut state).is_ok());
+        state.set_by_path("gain", 0.75).expect("valid value");
+        // Reaches the audio thread before the new dsp.
+        run(&mut dsp, &mut state, 1);
+        assert_eq!(dsp.dsp.values(), [0.5]);
+        run(&mut dsp, &mut state, 1);
+        assert_eq!(dsp.dsp.values(), [0.75]);
+        state.update();
+        assert_eq!(state.get_by_path("gain"), Some(&0.75));
+    }
+}
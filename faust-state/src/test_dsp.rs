@@ -0,0 +1,186 @@
+//! A dsp with widgets chosen by the tests, in place of one generated by faust.
+
+use faust_types::{FaustDsp, Meta, ParamIndex, TypedParamIndex, UI};
+use std::convert::TryFrom;
+
+#[derive(Debug, Clone, Copy)]
+pub enum Kind {
+    Button,
+    Slider {
+        init: f32,
+        min: f32,
+        max: f32,
+        step: f32,
+    },
+    Bargraph {
+        min: f32,
+        max: f32,
+    },
+}
+
+/// A widget with the labels of its groups below the root group, like `"group/label"`.
+#[derive(Debug, Clone)]
+pub struct TestWidget {
+    pub path: &'static str,
+    pub kind: Kind,
+    pub meta: Vec<(&'static str, &'static str)>,
+}
+
+/// Writes `level` to all outputs and all bargraphs with every block.
+#[derive(Debug, Clone)]
+pub struct TestDsp {
+    widgets: Vec<TestWidget>,
+    values: Vec<f32>,
+    outputs: i32,
+    sample_rate: i32,
+    pub level: f32,
+}
+
+pub const fn button(path: &'static str) -> TestWidget {
+    TestWidget {
+        path,
+        kind: Kind::Button,
+        meta: Vec::new(),
+    }
+}
+
+pub const fn slider(path: &'static str, init: f32, min: f32, max: f32, step: f32) -> TestWidget {
+    TestWidget {
+        path,
+        kind: Kind::Slider {
+            init,
+            min,
+            max,
+            step,
+        },
+        meta: Vec::new(),
+    }
+}
+
+pub const fn bargraph(path: &'static str, min: f32, max: f32) -> TestWidget {
+    TestWidget {
+        path,
+        kind: Kind::Bargraph { min, max },
+        meta: Vec::new(),
+    }
+}
+
+/// The typed index of the widget at position `idx`.
+pub fn param(idx: i32) -> TypedParamIndex<TestDsp> {
+    // SAFETY: the tests only pass positions of widgets they declared.
+    unsafe { TypedParamIndex::new_unchecked(ParamIndex(idx)) }
+}
+
+impl TestDsp {
+    /// The widget at position `n` has the index `n`.
+    pub fn with(outputs: i32, level: f32, widgets: Vec<TestWidget>) -> Self {
+        let values = widgets
+            .iter()
+            .map(|widget| match widget.kind {
+                Kind::Slider { init, .. } => init,
+                Kind::Button | Kind::Bargraph { .. } => 0.0,
+            })
+            .collect();
+        Self {
+            widgets,
+            values,
+            outputs,
+            sample_rate: 0,
+            level,
+        }
+    }
+
+    pub fn values(&self) -> &[f32] {
+        &self.values
+    }
+
+    fn value_mut(&mut self, param: ParamIndex) -> Option<&mut f32> {
+        usize::try_from(param.0)
+            .ok()
+            .and_then(move |idx| self.values.get_mut(idx))
+    }
+}
+
+impl FaustDsp for TestDsp {
+    type T = f32;
+
+    fn new() -> Self {
+        Self::with(1, 0.0, Vec::new())
+    }
+    fn metadata(&self, m: &mut dyn Meta) {
+        m.declare("name", "test");
+    }
+    fn get_sample_rate(&self) -> i32 {
+        self.sample_rate
+    }
+    fn get_num_inputs(&self) -> i32 {
+        0
+    }
+    fn get_num_outputs(&self) -> i32 {
+        self.outputs
+    }
+    fn class_init(_sample_rate: i32) {}
+    fn instance_reset_params(&mut self) {}
+    fn instance_clear(&mut self) {}
+    fn instance_constants(&mut self, sample_rate: i32) {
+        self.sample_rate = sample_rate;
+    }
+    fn instance_init(&mut self, sample_rate: i32) {
+        self.instance_constants(sample_rate);
+    }
+    fn init(&mut self, sample_rate: i32) {
+        self.instance_init(sample_rate);
+    }
+    fn build_user_interface(&self, ui_interface: &mut dyn UI<f32>) {
+        ui_interface.open_vertical_box("test");
+        for (idx, widget) in (0..).zip(&self.widgets) {
+            let mut labels: Vec<&str> = widget.path.split('/').collect();
+            let label = labels.pop().unwrap_or_default();
+            for group in &labels {
+                ui_interface.open_horizontal_box(group);
+            }
+            let param = ParamIndex(idx);
+            for (key, value) in &widget.meta {
+                ui_interface.declare(Some(param), key, value);
+            }
+            match widget.kind {
+                Kind::Button => ui_interface.add_button(label, param),
+                Kind::Slider {
+                    init,
+                    min,
+                    max,
+                    step,
+                } => ui_interface.add_horizontal_slider(label, param, init, min, max, step),
+                Kind::Bargraph { min, max } => {
+                    ui_interface.add_horizontal_bargraph(label, param, min, max);
+                }
+            }
+            for _ in &labels {
+                ui_interface.close_box();
+            }
+        }
+        ui_interface.close_box();
+    }
+    fn build_user_interface_static(_ui_interface: &mut dyn UI<f32>) {}
+    fn get_param(&self, param: ParamIndex) -> Option<f32> {
+        usize::try_from(param.0)
+            .ok()
+            .and_then(|idx| self.values.get(idx))
+            .copied()
+    }
+    fn set_param(&mut self, param: ParamIndex, value: f32) {
+        if let Some(current) = self.value_mut(param) {
+            *current = value;
+        }
+    }
+    fn compute(&mut self, count: i32, _inputs: &[&[f32]], outputs: &mut [&mut [f32]]) {
+        for output in outputs.iter_mut() {
+            output[..count as usize].fill(self.level);
+        }
+        for (widget, value) in self.widgets.iter().zip(&mut self.values) {
+            if let Kind::Bargraph { .. } = widget.kind {
+                *value = self.level;
+            }
+        }
+    }
+}
@@ -0,0 +1,196 @@
+use crate::{RangedInput, WidgetType};
+use faust_types::InvalidParamIndex;
+use std::{error::Error, fmt};
+
+/// How [`StateHandle::set_param`](crate::StateHandle::set_param) treats values outside
+/// the declared range and between the steps.
+///
+/// NaN, infinite values and writes to bargraphs are rejected in every mode.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ValueMode {
+    /// Rejects values outside the declared range, quantizes to the step.
+    Strict,
+    /// Clamps values to the declared range, quantizes to the step.
+    Clamp,
+    /// Passes values on unchanged, like before value modes existed.
+    #[default]
+    Raw,
+}
+
+/// A value rejected by [`StateHandle::set_param`](crate::StateHandle::set_param).
+#[derive(Debug, Clone, PartialEq)]
+pub enum ParamError {
+    /// The dsp has no parameter with this index.
+    InvalidIndex(InvalidParamIndex),
+    /// No parameter has this path or address.
+    NoSuchPath(String),
+    /// Bargraphs are set by the dsp.
+    Passive(i32),
+    /// NaN or infinite.
+    NotFinite { index: i32, value: f32 },
+    /// Outside the declared range, only in [`ValueMode::Strict`].
+    OutOfRange {
+        index: i32,
+        value: f32,
+        min: f32,
+        max: f32,
+    },
+}
+
+impl fmt::Display for ParamError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidIndex(err) => err.fmt(f),
+            Self::NoSuchPath(path) => write!(f, "no parameter with path {path}"),
+            Self::Passive(index) => write!(f, "parameter {index} is a bargraph set by the dsp"),
+            Self::NotFinite { index, value } => {
+                write!(f, "value {value} of parameter {index} is not finite")
+            }
+            Self::OutOfRange {
+                index,
+                value,
+                min,
+                max,
+            } => write!(
+                f,
+                "value {value} of parameter {index} is outside of {min}..={max}"
+            ),
+        }
+    }
+}
+
+impl Error for ParamError {}
+
+impl From<InvalidParamIndex> for ParamError {
+    fn from(err: InvalidParamIndex) -> Self {
+        Self::InvalidIndex(err)
+    }
+}
+
+/// Buttons and checkboxes are 0 or 1.
+const SWITCH: RangedInput = RangedInput {
+    init: 0.0,
+    range: 0.0..=1.0,
+    step: 1.0,
+};
+
+/// The value to store for `value` written to the parameter `index` of type `typ`.
+pub fn validate(
+    typ: &WidgetType,
+    index: i32,
+    value: f32,
+    mode: ValueMode,
+) -> Result<f32, ParamError> {
+    if typ.is_passive() {
+        return Err(ParamError::Passive(index));
+    }
+    if !value.is_finite() {
+        return Err(ParamError::NotFinite { index, value });
+    }
+    let input = match typ {
+        _ if mode == ValueMode::Raw => return Ok(value),
+        WidgetType::VerticalSlider(input)
+        | WidgetType::HorizontalSlider(input)
+        | WidgetType::NumEntry(input) => input,
+        WidgetType::Button | WidgetType::Toggle => &SWITCH,
+        WidgetType::HorizontalBarGraph(_)
+        | WidgetType::VerticalBargraph(_)
+        | WidgetType::Unknown => return Ok(value),
+    };
+    let (min, max) = (*input.range.start(), *input.range.end());
+    if mode == ValueMode::Strict && !input.range.contains(&value) {
+        return Err(ParamError::OutOfRange {
+            index,
+            value,
+            min,
+            max,
+        });
+    }
+    Ok(input.quantize(value.max(min).min(max)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{validate, ParamError, ValueMode};
+    use crate::{
+        test_dsp::{param, slider, TestDsp},
+        DspHandle, RangedInput, RangedOutput, WidgetType,
+    };
+
+    fn slider_type() -> WidgetType {
+        WidgetType::HorizontalSlider(RangedInput::new(1.0, 0.0, 10.0, 0.5))
+    }
+
+    #[test]
+    fn clamps_and_quantizes() {
+        let typ = slider_type();
+        assert_eq!(validate(&typ, 0, 3.3, ValueMode::Clamp), Ok(3.5));
+        assert_eq!(validate(&typ, 0, 12.0, ValueMode::Clamp), Ok(10.0));
+        assert_eq!(validate(&typ, 0, -1.0, ValueMode::Clamp), Ok(0.0));
+        assert_eq!(
+            validate(&WidgetType::Button, 0, 0.7, ValueMode::Clamp),
+            Ok(1.0)
+        );
+    }
+
+    #[test]
+    fn rejects_values_out_of_range_in_strict_mode() {
+        let typ = slider_type();
+        assert_eq!(validate(&typ, 0, 3.3, ValueMode::Strict), Ok(3.5));
+        assert_eq!(
+            validate(&typ, 2, 12.0, ValueMode::Strict),
+            Err(ParamError::OutOfRange {
+                index: 2,
+                value: 12.0,
+                min: 0.0,
+                max: 10.0
+            })
+        );
+    }
+
+    #[test]
+    fn refuses_nan_and_bargraphs_in_every_mode() {
+        let typ = slider_type();
+        let bargraph = WidgetType::VerticalBargraph(RangedOutput::new(-60.0, 0.0));
+        for mode in [ValueMode::Strict, ValueMode::Clamp, ValueMode::Raw] {
+            for value in [f32::NAN, f32::INFINITY, f32::NEG_INFINITY] {
+                assert!(matches!(
+                    validate(&typ, 0, value, mode),
+                    Err(ParamError::NotFinite { index: 0, .. })
+                ));
+            }
+            assert_eq!(
+                validate(&bargraph, 1, -6.0, mode),
+                Err(ParamError::Passive(1))
+            );
+        }
+    }
+
+    #[test]
+    fn passes_raw_values_on() {
+        let typ = slider_type();
+        assert_eq!(validate(&typ, 0, 12.3, ValueMode::Raw), Ok(12.3));
+        assert_eq!(validate(&typ, 0, 3.3, ValueMode::Raw), Ok(3.3));
+        assert_eq!(
+            validate(&WidgetType::Unknown, 0, 2.0, ValueMode::Strict),
+            Ok(2.0)
+        );
+    }
+
+    #[test]
+    fn state_handle_uses_the_value_mode() {
+        let dsp = TestDsp::with(1, 0.0, vec![slider("gain", 1.0, 0.0, 10.0, 0.5)]);
+        let (_dsp, mut state) = DspHandle::from_dsp(Box::new(dsp));
+        assert_eq!(state.value_mode(), ValueMode::Raw);
+        assert_eq!(state.set_param(param(0), 12.3), Ok(12.3));
+        assert!(state.set_param(param(0), f32::NAN).is_err());
+        assert_eq!(state.get_param(0), Some(&12.3));
+        state.set_value_mode(ValueMode::Clamp);
+        assert_eq!(state.set_param(param(0), 12.3), Ok(10.0));
+        assert_eq!(state.get_param(0), Some(&10.0));
+        assert!(matches!(
+            state.set_index(1, 0.0),
+            Err(ParamError::InvalidIndex(_))
+        ));
+    }
+}
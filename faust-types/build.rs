@@ -0,0 +1,18 @@
+use std::{env, process::Command};
+
+/// Passes the version of the compiler to `dynamic::ABI_VERSION`.
+fn main() {
+    let rustc = env::var_os("RUSTC").unwrap_or_else(|| "rustc".into());
+    let version = Command::new(rustc)
+        .arg("--version")
+        .output()
+        .ok()
+        .and_then(|output| String::from_utf8(output.stdout).ok())
+        .unwrap_or_default();
+    println!(
+        "cargo:rustc-env=FAUST_TYPES_RUSTC_VERSION={}",
+        version.trim()
+    );
+    println!("cargo:rerun-if-changed=build.rs");
+    println!("cargo:rerun-if-env-changed=RUSTC");
+}
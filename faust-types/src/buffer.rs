@@ -0,0 +1,427 @@
+//! Adapters for audio buffers not laid out as one slice per channel.
+//!
+//! Both adapters copy through preallocated scratch buffers,
+//! so processing a block does not allocate for up to [`STACK_CHANNELS`] channels.
+
+use crate::{dynamic::DynFaustDsp, ComputeError};
+use std::convert::TryFrom;
+
+/// Channels per direction handled without allocating the slice lists passed to the dsp.
+pub const STACK_CHANNELS: usize = 32;
+
+/// One preallocated buffer per channel, to be filled and read by the caller.
+///
+/// ```
+/// # use faust_types::{buffer::ChannelBuffers, dynamic::DynFaustDsp, ComputeError};
+/// # fn run(
+/// #     dsp: &mut dyn DynFaustDsp,
+/// #     left: &[f32],
+/// #     right: &[f32],
+/// #     left_out: &mut [f32],
+/// # ) -> Result<(), ComputeError> {
+/// # let len = left.len();
+/// let mut buffers = ChannelBuffers::new(2, 2, 1024);
+/// buffers.input_mut(0)[..len].copy_from_slice(left);
+/// buffers.input_mut(1)[..len].copy_from_slice(right);
+/// buffers.process(dsp, len)?;
+/// left_out.copy_from_slice(&buffers.output(0)[..len]);
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct ChannelBuffers<T> {
+    inputs: Vec<Vec<T>>,
+    outputs: Vec<Vec<T>>,
+    max_frames: usize,
+}
+
+impl<T: Copy + Default> ChannelBuffers<T> {
+    #[must_use]
+    pub fn new(num_inputs: usize, num_outputs: usize, max_frames: usize) -> Self {
+        Self {
+            inputs: vec![vec![T::default(); max_frames]; num_inputs],
+            outputs: vec![vec![T::default(); max_frames]; num_outputs],
+            max_frames,
+        }
+    }
+
+    /// Buffers for the channels of `dsp`.
+    #[must_use]
+    pub fn for_dsp(dsp: &(impl DynFaustDsp<T> + ?Sized), max_frames: usize) -> Self {
+        Self::new(
+            channels(dsp.get_num_inputs()),
+            channels(dsp.get_num_outputs()),
+            max_frames,
+        )
+    }
+}
+
+impl<T> ChannelBuffers<T> {
+    #[must_use]
+    pub fn num_inputs(&self) -> usize {
+        self.inputs.len()
+    }
+
+    #[must_use]
+    pub fn num_outputs(&self) -> usize {
+        self.outputs.len()
+    }
+
+    #[must_use]
+    pub fn max_frames(&self) -> usize {
+        self.max_frames
+    }
+
+    /// The whole buffer of an input channel, `max_frames` long.
+    ///
+    /// # Panics
+    /// If the channel does not exist.
+    pub fn input_mut(&mut self, channel: usize) -> &mut [T] {
+        &mut self.inputs[channel]
+    }
+
+    /// The whole buffer of an output channel, `max_frames` long.
+    ///
+    /// # Panics
+    /// If the channel does not exist.
+    #[must_use]
+    pub fn output(&self, channel: usize) -> &[T] {
+        &self.outputs[channel]
+    }
+
+    /// Computes the first `frames` frames of the buffers.
+    ///
+    /// # Errors
+    /// If `frames` exceeds `max_frames` or the channels don't match the dsp.
+    pub fn process(
+        &mut self,
+        dsp: &mut (impl DynFaustDsp<T> + ?Sized),
+        frames: usize,
+    ) -> Result<(), ComputeError> {
+        self.process_with(frames, |inputs, outputs| dsp.process(inputs, outputs))
+    }
+
+    /// Passes the first `frames` frames of the buffers to `compute`,
+    /// for example `|inputs, outputs| handle.update_and_process(inputs, outputs)`.
+    ///
+    /// # Errors
+    /// If `frames` exceeds `max_frames` or `compute` fails.
+    pub fn process_with<F>(&mut self, frames: usize, compute: F) -> Result<(), ComputeError>
+    where
+        F: FnOnce(&[&[T]], &mut [&mut [T]]) -> Result<(), ComputeError>,
+    {
+        if frames > self.max_frames {
+            return Err(ComputeError::BlockTooLong {
+                max: self.max_frames,
+                found: frames,
+            });
+        }
+        let (inputs, outputs) = (&self.inputs, &mut self.outputs);
+        let (num_inputs, num_outputs) = (inputs.len(), outputs.len());
+        if num_inputs <= STACK_CHANNELS && num_outputs <= STACK_CHANNELS {
+            let mut input_refs: [&[T]; STACK_CHANNELS] = [&[]; STACK_CHANNELS];
+            for (input_ref, input) in input_refs.iter_mut().zip(inputs) {
+                *input_ref = &input[..frames];
+            }
+            let mut output_refs: [&mut [T]; STACK_CHANNELS] = std::array::from_fn(|_| &mut [][..]);
+            for (output_ref, output) in output_refs.iter_mut().zip(outputs.iter_mut()) {
+                *output_ref = &mut output[..frames];
+            }
+            compute(&input_refs[..num_inputs], &mut output_refs[..num_outputs])
+        } else {
+            let input_refs: Vec<&[T]> = inputs.iter().map(|input| &input[..frames]).collect();
+            let mut output_refs: Vec<&mut [T]> = outputs
+                .iter_mut()
+                .map(|output| &mut output[..frames])
+                .collect();
+            compute(&input_refs, &mut output_refs)
+        }
+    }
+}
+
+/// Processes interleaved buffers, `[l0, r0, l1, r1, ..]`, or `[T; N]` frames.
+///
+/// Blocks longer than `max_frames` are computed in several chunks.
+///
+/// ```
+/// # use faust_types::{buffer::Interleaved, dynamic::DynFaustDsp, ComputeError};
+/// # fn run(dsp: &mut dyn DynFaustDsp, input: &[f32], output: &mut [f32]) -> Result<(), ComputeError> {
+/// let mut interleaved = Interleaved::for_dsp(dsp, 256);
+/// interleaved.process(dsp, input, output)?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct Interleaved<T> {
+    buffers: ChannelBuffers<T>,
+}
+
+impl<T: Copy + Default> Interleaved<T> {
+    /// # Panics
+    /// If `max_frames` is 0.
+    #[must_use]
+    pub fn new(num_inputs: usize, num_outputs: usize, max_frames: usize) -> Self {
+        assert!(max_frames > 0, "max_frames must not be 0");
+        Self {
+            buffers: ChannelBuffers::new(num_inputs, num_outputs, max_frames),
+        }
+    }
+
+    /// Scratch space for the channels of `dsp`.
+    ///
+    /// # Panics
+    /// If `max_frames` is 0.
+    #[must_use]
+    pub fn for_dsp(dsp: &(impl DynFaustDsp<T> + ?Sized), max_frames: usize) -> Self {
+        Self::new(
+            channels(dsp.get_num_inputs()),
+            channels(dsp.get_num_outputs()),
+            max_frames,
+        )
+    }
+
+    /// # Errors
+    /// If the buffers don't hold whole frames, differ in the number of frames
+    /// or the channels don't match the dsp.
+    pub fn process(
+        &mut self,
+        dsp: &mut (impl DynFaustDsp<T> + ?Sized),
+        input: &[T],
+        output: &mut [T],
+    ) -> Result<(), ComputeError> {
+        self.process_with(input, output, |inputs, outputs| {
+            dsp.process(inputs, outputs)
+        })
+    }
+
+    /// Processes `[T; I]` input frames into `[T; O]` output frames.
+    ///
+    /// # Errors
+    /// If the number of frames differs or the channels don't match the dsp.
+    pub fn process_frames<const I: usize, const O: usize>(
+        &mut self,
+        dsp: &mut (impl DynFaustDsp<T> + ?Sized),
+        input: &[[T; I]],
+        output: &mut [[T; O]],
+    ) -> Result<(), ComputeError> {
+        self.process(dsp, input.as_flattened(), output.as_flattened_mut())
+    }
+
+    /// Passes each deinterleaved chunk to `compute`,
+    /// for example `|inputs, outputs| handle.update_and_process(inputs, outputs)`.
+    ///
+    /// # Errors
+    /// If the buffers don't hold whole frames, differ in the number of frames or `compute` fails.
+    pub fn process_with<F>(
+        &mut self,
+        input: &[T],
+        output: &mut [T],
+        mut compute: F,
+    ) -> Result<(), ComputeError>
+    where
+        F: FnMut(&[&[T]], &mut [&mut [T]]) -> Result<(), ComputeError>,
+    {
+        let num_inputs = self.buffers.num_inputs();
+        let num_outputs = self.buffers.num_outputs();
+        let input_frames = whole_frames(input.len(), num_inputs)?;
+        let output_frames = whole_frames(output.len(), num_outputs)?;
+        let frames = match (input_frames, output_frames) {
+            (Some(input_frames), Some(output_frames)) if input_frames != output_frames => {
+                return Err(ComputeError::FrameCount {
+                    expected: input_frames,
+                    found: output_frames,
+                });
+            }
+            (Some(frames), _) | (None, Some(frames)) => frames,
+            (None, None) => 0,
+        };
+        let max_frames = self.buffers.max_frames();
+        let mut start = 0;
+        while start < frames {
+            let len = max_frames.min(frames - start);
+            for (channel, input_buffer) in self.buffers.inputs.iter_mut().enumerate() {
+                let samples = input[start * num_inputs..].iter().skip(channel);
+                for (sample, frame) in input_buffer[..len]
+                    .iter_mut()
+                    .zip(samples.step_by(num_inputs))
+                {
+                    *sample = *frame;
+                }
+            }
+            self.buffers.process_with(len, &mut compute)?;
+            for (channel, output_buffer) in self.buffers.outputs.iter().enumerate() {
+                let samples = output[start * num_outputs..].iter_mut().skip(channel);
+                for (frame, sample) in samples.step_by(num_outputs).zip(&output_buffer[..len]) {
+                    *frame = *sample;
+                }
+            }
+            start += len;
+        }
+        Ok(())
+    }
+}
+
+impl<T> Interleaved<T> {
+    #[must_use]
+    pub fn num_inputs(&self) -> usize {
+        self.buffers.num_inputs()
+    }
+
+    #[must_use]
+    pub fn num_outputs(&self) -> usize {
+        self.buffers.num_outputs()
+    }
+
+    #[must_use]
+    pub fn max_frames(&self) -> usize {
+        self.buffers.max_frames()
+    }
+}
+
+/// The number of frames of an interleaved buffer, `None` without channels.
+fn whole_frames(len: usize, channels: usize) -> Result<Option<usize>, ComputeError> {
+    if channels == 0 && len == 0 {
+        return Ok(None);
+    }
+    if channels > 0 && len % channels == 0 {
+        Ok(Some(len / channels))
+    } else {
+        Err(ComputeError::PartialFrame { len, channels })
+    }
+}
+
+fn channels(count: i32) -> usize {
+    usize::try_from(count).unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ChannelBuffers, Interleaved, STACK_CHANNELS};
+    use crate::{test_dsp::Stereo, ComputeError, FaustDsp, ParamIndex};
+
+    fn stereo(gain: f32) -> Stereo {
+        let mut dsp = Stereo::new();
+        dsp.init(48000);
+        dsp.set_param(ParamIndex(0), gain);
+        dsp
+    }
+
+    #[test]
+    fn processes_the_first_frames_of_the_channel_buffers() {
+        let mut dsp = stereo(2.0);
+        let mut buffers = ChannelBuffers::for_dsp(&dsp, 4);
+        assert_eq!((buffers.num_inputs(), buffers.num_outputs()), (2, 2));
+        buffers.input_mut(0).copy_from_slice(&[1.0, 2.0, 3.0, 4.0]);
+        buffers.input_mut(1).copy_from_slice(&[5.0, 6.0, 7.0, 8.0]);
+        assert_eq!(buffers.process(&mut dsp, 3), Ok(()));
+        assert_eq!(buffers.output(0), [2.0, 4.0, 6.0, 0.0]);
+        assert_eq!(buffers.output(1), [10.0, 12.0, 14.0, 0.0]);
+        assert_eq!(
+            buffers.process(&mut dsp, 5),
+            Err(ComputeError::BlockTooLong { max: 4, found: 5 })
+        );
+    }
+
+    #[test]
+    fn rejects_channel_buffers_not_matching_the_dsp() {
+        let mut dsp = stereo(1.0);
+        let mut buffers = ChannelBuffers::new(1, 2, 4);
+        assert_eq!(
+            buffers.process(&mut dsp, 4),
+            Err(ComputeError::Inputs {
+                expected: 2,
+                found: 1
+            })
+        );
+    }
+
+    #[test]
+    fn passes_more_channels_than_fit_on_the_stack() {
+        let channels = STACK_CHANNELS + 1;
+        let mut buffers = ChannelBuffers::new(channels, channels, 2);
+        for channel in 0..channels {
+            buffers.input_mut(channel).fill(channel as f32);
+        }
+        let result = buffers.process_with(1, |inputs, outputs| {
+            assert_eq!((inputs.len(), outputs.len()), (channels, channels));
+            for (input, output) in inputs.iter().zip(outputs.iter_mut()) {
+                output.copy_from_slice(input);
+            }
+            Ok(())
+        });
+        assert_eq!(result, Ok(()));
+        assert_eq!(buffers.output(STACK_CHANNELS), [STACK_CHANNELS as f32, 0.0]);
+    }
+
+    #[test]
+    fn processes_interleaved_buffers_in_chunks() {
+        let mut dsp = stereo(0.5);
+        let mut interleaved = Interleaved::for_dsp(&dsp, 2);
+        let input: Vec<f32> = (0..10).map(|sample| sample as f32).collect();
+        let mut output = [0.0; 10];
+        let mut chunks = Vec::new();
+        let result = interleaved.process_with(&input, &mut output, |inputs, outputs| {
+            chunks.push(inputs[0].len());
+            dsp.process(inputs, outputs)
+        });
+        assert_eq!(result, Ok(()));
+        assert_eq!(chunks, [2, 2, 1]);
+        let expected: Vec<f32> = input.iter().map(|sample| sample * 0.5).collect();
+        assert_eq!(output[..], expected[..]);
+    }
+
+    #[test]
+    fn processes_frames() {
+        let mut dsp = stereo(2.0);
+        let mut interleaved = Interleaved::for_dsp(&dsp, 4);
+        let mut output = [[0.0; 2]; 3];
+        let input = [[1.0, -1.0], [2.0, -2.0], [3.0, -3.0]];
+        assert_eq!(
+            interleaved.process_frames(&mut dsp, &input, &mut output),
+            Ok(())
+        );
+        assert_eq!(output, [[2.0, -2.0], [4.0, -4.0], [6.0, -6.0]]);
+    }
+
+    #[test]
+    fn rejects_partial_and_differing_frames() {
+        let mut dsp = stereo(1.0);
+        let mut interleaved = Interleaved::for_dsp(&dsp, 4);
+        assert_eq!(
+            interleaved.process(&mut dsp, &[0.0; 5], &mut [0.0; 6]),
+            Err(ComputeError::PartialFrame {
+                len: 5,
+                channels: 2
+            })
+        );
+        assert_eq!(
+            interleaved.process(&mut dsp, &[0.0; 4], &mut [0.0; 6]),
+            Err(ComputeError::FrameCount {
+                expected: 2,
+                found: 3
+            })
+        );
+    }
+
+    #[test]
+    fn takes_the_frames_from_the_buffers_with_channels() {
+        let mut generator = Interleaved::<f32>::new(0, 2, 4);
+        let mut output = [0.0; 6];
+        let mut frames = 0;
+        let result = generator.process_with(&[], &mut output, |_, outputs| {
+            frames += outputs[0].len();
+            outputs[1].fill(1.0);
+            Ok(())
+        });
+        assert_eq!(result, Ok(()));
+        assert_eq!(frames, 3);
+        assert_eq!(output, [0.0, 1.0, 0.0, 1.0, 0.0, 1.0]);
+        assert_eq!(
+            generator.process_with(&[0.0], &mut output, |_, _| Ok(())),
+            Err(ComputeError::PartialFrame {
+                len: 1,
+                channels: 0
+            })
+        );
+    }
+}
@@ -0,0 +1,474 @@
+//! Collectors of the user interface declared by a dsp.
+//!
+//! - [`LayoutTree`] keeps the groups with their kind and metadata, and the order of the widgets.
+//! - [`ParamList`] is the flat list of widgets in declaration order.
+//! - [`PathMap`] finds widgets by path, the labels of the enclosing groups and the widget joined by `/`.
+//!
+//! ```
+//! # use faust_types::{collect::{LayoutTree, ParamList, PathMap}, FaustDsp};
+//! # fn run<MyDsp: FaustDsp<T = f32>>(dsp: &MyDsp) {
+//! let tree = LayoutTree::from_static::<MyDsp>();
+//! let params = ParamList::from_dsp(dsp);
+//! let gain = PathMap::from(params).get("mydsp/gain").map(|widget| widget.index);
+//! # }
+//! ```
+
+use crate::{dynamic::DynFaustDsp, FaustDsp, ParamIndex, UI};
+use std::collections::{BTreeMap, HashMap};
+
+/// The layout groups of faust, `tgroup`, `hgroup` and `vgroup`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum GroupKind {
+    Tab,
+    Horizontal,
+    Vertical,
+}
+
+/// Kind and declared range of a widget.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum WidgetKind<T> {
+    Button,
+    CheckButton,
+    VerticalSlider { init: T, min: T, max: T, step: T },
+    HorizontalSlider { init: T, min: T, max: T, step: T },
+    NumEntry { init: T, min: T, max: T, step: T },
+    HorizontalBargraph { min: T, max: T },
+    VerticalBargraph { min: T, max: T },
+}
+
+impl<T> WidgetKind<T> {
+    /// Bargraphs are passive, their value is set by the dsp.
+    #[must_use]
+    pub const fn is_passive(&self) -> bool {
+        matches!(
+            self,
+            Self::HorizontalBargraph { .. } | Self::VerticalBargraph { .. }
+        )
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Widget<T> {
+    pub index: ParamIndex,
+    pub label: String,
+    /// Labels of the enclosing groups, outermost first.
+    pub groups: Vec<String>,
+    pub kind: WidgetKind<T>,
+    /// Metadata in declaration order.
+    pub metadata: Vec<(String, String)>,
+}
+
+impl<T> Widget<T> {
+    /// The labels of the groups and the widget joined by `/`.
+    #[must_use]
+    pub fn path(&self) -> String {
+        let mut path = self.groups.join("/");
+        if !path.is_empty() {
+            path.push('/');
+        }
+        path.push_str(&self.label);
+        path
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Group<T> {
+    pub kind: GroupKind,
+    pub label: String,
+    /// Metadata in declaration order.
+    pub metadata: Vec<(String, String)>,
+    pub items: Vec<LayoutItem<T>>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum LayoutItem<T> {
+    Group(Group<T>),
+    Widget(Widget<T>),
+}
+
+/// The complete user interface, built by `build_user_interface`.
+#[derive(Debug, Clone)]
+pub struct LayoutTree<T> {
+    items: Vec<LayoutItem<T>>,
+    open: Vec<Group<T>>,
+    group_metadata: Vec<(String, String)>,
+    widget_metadata: HashMap<ParamIndex, Vec<(String, String)>>,
+}
+
+impl<T> Default for LayoutTree<T> {
+    fn default() -> Self {
+        Self {
+            items: Vec::new(),
+            open: Vec::new(),
+            group_metadata: Vec::new(),
+            widget_metadata: HashMap::new(),
+        }
+    }
+}
+
+impl<T> LayoutTree<T> {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    #[must_use]
+    pub fn from_dsp(dsp: &(impl DynFaustDsp<T> + ?Sized)) -> Self {
+        let mut tree = Self::new();
+        dsp.build_user_interface(&mut tree);
+        tree
+    }
+
+    #[must_use]
+    pub fn from_static<D: FaustDsp<T = T>>() -> Self {
+        let mut tree = Self::new();
+        D::build_user_interface_static(&mut tree);
+        tree
+    }
+
+    /// The top level items, usually a single group.
+    #[must_use]
+    pub fn items(&self) -> &[LayoutItem<T>] {
+        &self.items
+    }
+
+    #[must_use]
+    pub fn into_items(self) -> Vec<LayoutItem<T>> {
+        self.items
+    }
+
+    /// All widgets in declaration order.
+    #[must_use]
+    pub fn widgets(&self) -> Vec<&Widget<T>> {
+        fn visit<'a, T>(items: &'a [LayoutItem<T>], widgets: &mut Vec<&'a Widget<T>>) {
+            for item in items {
+                match item {
+                    LayoutItem::Group(group) => visit(&group.items, widgets),
+                    LayoutItem::Widget(widget) => widgets.push(widget),
+                }
+            }
+        }
+        let mut widgets = Vec::new();
+        visit(&self.items, &mut widgets);
+        widgets
+    }
+
+    fn current_items(&mut self) -> &mut Vec<LayoutItem<T>> {
+        match self.open.last_mut() {
+            Some(group) => &mut group.items,
+            None => &mut self.items,
+        }
+    }
+
+    fn open_group(&mut self, kind: GroupKind, label: &str) {
+        self.open.push(Group {
+            kind,
+            label: label.to_owned(),
+            metadata: std::mem::take(&mut self.group_metadata),
+            items: Vec::new(),
+        });
+    }
+
+    fn add_widget(&mut self, label: &str, index: ParamIndex, kind: WidgetKind<T>) {
+        let widget = Widget {
+            index,
+            label: label.to_owned(),
+            groups: self.open.iter().map(|group| group.label.clone()).collect(),
+            kind,
+            metadata: self.widget_metadata.remove(&index).unwrap_or_default(),
+        };
+        self.current_items().push(LayoutItem::Widget(widget));
+    }
+}
+
+impl<T> UI<T> for LayoutTree<T> {
+    fn open_tab_box(&mut self, label: &str) {
+        self.open_group(GroupKind::Tab, label);
+    }
+    fn open_horizontal_box(&mut self, label: &str) {
+        self.open_group(GroupKind::Horizontal, label);
+    }
+    fn open_vertical_box(&mut self, label: &str) {
+        self.open_group(GroupKind::Vertical, label);
+    }
+    fn close_box(&mut self) {
+        if let Some(group) = self.open.pop() {
+            self.current_items().push(LayoutItem::Group(group));
+        }
+    }
+
+    fn add_button(&mut self, label: &str, param: ParamIndex) {
+        self.add_widget(label, param, WidgetKind::Button);
+    }
+    fn add_check_button(&mut self, label: &str, param: ParamIndex) {
+        self.add_widget(label, param, WidgetKind::CheckButton);
+    }
+    fn add_vertical_slider(
+        &mut self,
+        label: &str,
+        param: ParamIndex,
+        init: T,
+        min: T,
+        max: T,
+        step: T,
+    ) {
+        let kind = WidgetKind::VerticalSlider {
+            init,
+            min,
+            max,
+            step,
+        };
+        self.add_widget(label, param, kind);
+    }
+    fn add_horizontal_slider(
+        &mut self,
+        label: &str,
+        param: ParamIndex,
+        init: T,
+        min: T,
+        max: T,
+        step: T,
+    ) {
+        let kind = WidgetKind::HorizontalSlider {
+            init,
+            min,
+            max,
+            step,
+        };
+        self.add_widget(label, param, kind);
+    }
+    fn add_num_entry(&mut self, label: &str, param: ParamIndex, init: T, min: T, max: T, step: T) {
+        let kind = WidgetKind::NumEntry {
+            init,
+            min,
+            max,
+            step,
+        };
+        self.add_widget(label, param, kind);
+    }
+
+    fn add_horizontal_bargraph(&mut self, label: &str, param: ParamIndex, min: T, max: T) {
+        self.add_widget(label, param, WidgetKind::HorizontalBargraph { min, max });
+    }
+    fn add_vertical_bargraph(&mut self, label: &str, param: ParamIndex, min: T, max: T) {
+        self.add_widget(label, param, WidgetKind::VerticalBargraph { min, max });
+    }
+
+    /// Metadata is declared before the group or widget it belongs to,
+    /// metadata of a widget declared afterwards is added as long as its group is open.
+    fn declare(&mut self, param: Option<ParamIndex>, key: &str, value: &str) {
+        let entry = (key.to_owned(), value.to_owned());
+        let Some(index) = param else {
+            self.group_metadata.push(entry);
+            return;
+        };
+        let added = self.current_items().iter_mut().find_map(|item| match item {
+            LayoutItem::Widget(widget) if widget.index == index => Some(widget),
+            _ => None,
+        });
+        match added {
+            Some(widget) => widget.metadata.push(entry),
+            None => self.widget_metadata.entry(index).or_default().push(entry),
+        }
+    }
+}
+
+/// All widgets in declaration order.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParamList<T> {
+    widgets: Vec<Widget<T>>,
+}
+
+impl<T> ParamList<T> {
+    #[must_use]
+    pub fn from_dsp(dsp: &(impl DynFaustDsp<T> + ?Sized)) -> Self {
+        LayoutTree::from_dsp(dsp).into()
+    }
+
+    #[must_use]
+    pub fn from_static<D: FaustDsp<T = T>>() -> Self {
+        LayoutTree::from_static::<D>().into()
+    }
+
+    #[must_use]
+    pub fn widgets(&self) -> &[Widget<T>] {
+        &self.widgets
+    }
+
+    #[must_use]
+    pub fn into_widgets(self) -> Vec<Widget<T>> {
+        self.widgets
+    }
+
+    #[must_use]
+    pub fn get(&self, index: ParamIndex) -> Option<&Widget<T>> {
+        self.widgets.iter().find(|widget| widget.index == index)
+    }
+}
+
+impl<T> From<LayoutTree<T>> for ParamList<T> {
+    fn from(tree: LayoutTree<T>) -> Self {
+        fn flatten<T>(items: Vec<LayoutItem<T>>, widgets: &mut Vec<Widget<T>>) {
+            for item in items {
+                match item {
+                    LayoutItem::Group(group) => flatten(group.items, widgets),
+                    LayoutItem::Widget(widget) => widgets.push(widget),
+                }
+            }
+        }
+        let mut widgets = Vec::new();
+        flatten(tree.into_items(), &mut widgets);
+        Self { widgets }
+    }
+}
+
+impl<T> IntoIterator for ParamList<T> {
+    type Item = Widget<T>;
+    type IntoIter = std::vec::IntoIter<Widget<T>>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.widgets.into_iter()
+    }
+}
+
+/// The widgets by [`Widget::path`].
+///
+/// Of widgets with the same path only the last one is kept.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PathMap<T> {
+    widgets: BTreeMap<String, Widget<T>>,
+}
+
+impl<T> PathMap<T> {
+    #[must_use]
+    pub fn from_dsp(dsp: &(impl DynFaustDsp<T> + ?Sized)) -> Self {
+        ParamList::from_dsp(dsp).into()
+    }
+
+    #[must_use]
+    pub fn from_static<D: FaustDsp<T = T>>() -> Self {
+        ParamList::from_static::<D>().into()
+    }
+
+    #[must_use]
+    pub fn get(&self, path: &str) -> Option<&Widget<T>> {
+        self.widgets.get(path)
+    }
+
+    /// The widgets sorted by path.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &Widget<T>)> {
+        self.widgets
+            .iter()
+            .map(|(path, widget)| (path.as_str(), widget))
+    }
+}
+
+impl<T> From<ParamList<T>> for PathMap<T> {
+    fn from(params: ParamList<T>) -> Self {
+        let widgets = params
+            .into_iter()
+            .map(|widget| (widget.path(), widget))
+            .collect();
+        Self { widgets }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{GroupKind, LayoutItem, LayoutTree, ParamList, PathMap, WidgetKind};
+    use crate::{test_dsp::Stereo, ParamIndex, UI};
+
+    fn metadata(entries: &[(&str, &str)]) -> Vec<(String, String)> {
+        entries
+            .iter()
+            .map(|(key, value)| ((*key).to_owned(), (*value).to_owned()))
+            .collect()
+    }
+
+    #[test]
+    fn keeps_groups_and_metadata() {
+        let tree = LayoutTree::from_static::<Stereo>();
+        let [LayoutItem::Group(root)] = tree.items() else {
+            panic!("expected a single root group: {:?}", tree.items());
+        };
+        assert_eq!(
+            (root.kind, root.label.as_str()),
+            (GroupKind::Vertical, "stereo")
+        );
+        assert!(root.metadata.is_empty());
+        let [LayoutItem::Group(out), LayoutItem::Widget(mute)] = &root.items[..] else {
+            panic!("expected a group and a widget: {:?}", root.items);
+        };
+        assert_eq!(
+            (out.kind, out.label.as_str()),
+            (GroupKind::Horizontal, "out")
+        );
+        assert_eq!(out.metadata, metadata(&[("0", "")]));
+        assert_eq!(out.items.len(), 2);
+        assert_eq!(mute.kind, WidgetKind::Button);
+        assert_eq!(mute.groups, ["stereo"]);
+    }
+
+    #[test]
+    fn adds_metadata_declared_before_and_after_the_widget() {
+        let widgets = ParamList::from_static::<Stereo>();
+        let gain = widgets.get(ParamIndex(0)).expect("gain");
+        assert_eq!(gain.metadata, metadata(&[("unit", "dB")]));
+        let level = widgets.get(ParamIndex(1)).expect("level");
+        assert_eq!(level.metadata, metadata(&[("style", "led")]));
+        assert!(level.kind.is_passive());
+        assert!(!gain.kind.is_passive());
+    }
+
+    #[test]
+    fn lists_widgets_in_declaration_order() {
+        let tree = LayoutTree::from_dsp(&Stereo::default());
+        let paths: Vec<_> = tree.widgets().iter().map(|widget| widget.path()).collect();
+        assert_eq!(
+            paths,
+            ["stereo/out/gain", "stereo/out/level", "stereo/mute"]
+        );
+        let params = ParamList::from(tree);
+        assert_eq!(params, ParamList::from_static::<Stereo>());
+        assert!(params.get(ParamIndex(3)).is_none());
+        let indices: Vec<_> = params.into_iter().map(|widget| widget.index.0).collect();
+        assert_eq!(indices, [0, 1, 2]);
+    }
+
+    #[test]
+    fn finds_widgets_by_path() {
+        let paths = PathMap::from_static::<Stereo>();
+        let gain = paths.get("stereo/out/gain").expect("gain");
+        assert_eq!(
+            gain.kind,
+            WidgetKind::HorizontalSlider {
+                init: 1.0,
+                min: 0.0,
+                max: 2.0,
+                step: 0.01
+            }
+        );
+        assert!(paths.get("out/gain").is_none());
+        let sorted: Vec<_> = paths.iter().map(|(path, _)| path).collect();
+        assert_eq!(
+            sorted,
+            ["stereo/mute", "stereo/out/gain", "stereo/out/level"]
+        );
+    }
+
+    #[test]
+    fn keeps_the_last_widget_of_a_path() {
+        let mut tree = LayoutTree::<f32>::new();
+        tree.open_tab_box("tabs");
+        tree.add_check_button("on", ParamIndex(0));
+        tree.add_check_button("on", ParamIndex(1));
+        tree.close_box();
+        tree.add_button("top", ParamIndex(2));
+        let paths = PathMap::from(ParamList::from(tree));
+        assert_eq!(
+            paths.get("tabs/on").map(|widget| widget.index),
+            Some(ParamIndex(1))
+        );
+        assert_eq!(paths.get("top").map(|widget| widget.groups.len()), Some(0));
+    }
+}
@@ -0,0 +1,235 @@
+//! Object safe counterparts of [`FaustDsp`] for dsps of different types in one collection.
+//!
+//! Not part of the crate root, as calling a method on a type implementing both
+//! [`FaustDsp`] and [`DynFaustDsp`] is ambiguous when both traits are in scope,
+//! and generated code imports `faust_types::*`.
+
+use crate::{frame_count, ComputeError, FaustDsp, InvalidParamIndex, Meta, ParamIndex, UI};
+use std::{fmt, marker::PhantomData};
+
+/// The version of faust-types and of the compiler that built it.
+///
+/// `DynFaustDsp` trait objects can only be passed between code built with the same
+/// faust-types and compiler, so a dynamic library exports it and the loader compares it
+/// with its own before using the library.
+pub const ABI_VERSION: &str = concat!(
+    env!("CARGO_PKG_VERSION"),
+    " ",
+    env!("FAUST_TYPES_RUSTC_VERSION")
+);
+
+/// [`ABI_VERSION`] with a terminating nul byte, as returned by the exported function.
+#[doc(hidden)]
+pub const ABI_VERSION_NUL: &str = concat!(
+    env!("CARGO_PKG_VERSION"),
+    " ",
+    env!("FAUST_TYPES_RUSTC_VERSION"),
+    "\0"
+);
+
+/// The per-instance operations of [`FaustDsp`].
+///
+/// Object safe, so dsps of different types can be stored as `Box<dyn DynFaustDsp>`.
+/// Implemented for every [`FaustDsp`].
+pub trait DynFaustDsp<T = f32> {
+    fn metadata(&self, m: &mut dyn Meta);
+    fn get_sample_rate(&self) -> i32;
+    fn get_num_inputs(&self) -> i32;
+    fn get_num_outputs(&self) -> i32;
+    fn instance_reset_params(&mut self);
+    fn instance_clear(&mut self);
+    fn instance_constants(&mut self, sample_rate: i32);
+    fn instance_init(&mut self, sample_rate: i32);
+    fn init(&mut self, sample_rate: i32);
+    fn build_user_interface(&self, ui_interface: &mut dyn UI<T>);
+    fn get_param(&self, param: ParamIndex) -> Option<T>;
+    fn set_param(&mut self, param: ParamIndex, value: T);
+    fn compute(&mut self, count: i32, inputs: &[&[T]], outputs: &mut [&mut [T]]);
+
+    /// See [`FaustDsp::try_set_param`].
+    ///
+    /// # Errors
+    /// If [`get_param`](Self::get_param) returns `None` for the index.
+    fn try_set_param(&mut self, param: ParamIndex, value: T) -> Result<(), InvalidParamIndex> {
+        self.try_get_param(param)?;
+        self.set_param(param, value);
+        Ok(())
+    }
+
+    /// See [`FaustDsp::try_get_param`].
+    ///
+    /// # Errors
+    /// If [`get_param`](Self::get_param) returns `None` for the index.
+    fn try_get_param(&self, param: ParamIndex) -> Result<T, InvalidParamIndex> {
+        self.get_param(param).ok_or(InvalidParamIndex(param))
+    }
+
+    /// See [`FaustDsp::process`].
+    ///
+    /// # Errors
+    /// See [`frame_count`].
+    fn process(&mut self, inputs: &[&[T]], outputs: &mut [&mut [T]]) -> Result<(), ComputeError> {
+        let count = frame_count(
+            self.get_num_inputs(),
+            self.get_num_outputs(),
+            inputs,
+            outputs,
+        )?;
+        if count > 0 {
+            self.compute(count, inputs, outputs);
+        }
+        Ok(())
+    }
+}
+
+impl<D> DynFaustDsp<D::T> for D
+where
+    D: FaustDsp + ?Sized,
+{
+    fn metadata(&self, m: &mut dyn Meta) {
+        FaustDsp::metadata(self, m);
+    }
+    fn get_sample_rate(&self) -> i32 {
+        FaustDsp::get_sample_rate(self)
+    }
+    fn get_num_inputs(&self) -> i32 {
+        FaustDsp::get_num_inputs(self)
+    }
+    fn get_num_outputs(&self) -> i32 {
+        FaustDsp::get_num_outputs(self)
+    }
+    fn instance_reset_params(&mut self) {
+        FaustDsp::instance_reset_params(self);
+    }
+    fn instance_clear(&mut self) {
+        FaustDsp::instance_clear(self);
+    }
+    fn instance_constants(&mut self, sample_rate: i32) {
+        FaustDsp::instance_constants(self, sample_rate);
+    }
+    fn instance_init(&mut self, sample_rate: i32) {
+        FaustDsp::instance_init(self, sample_rate);
+    }
+    fn init(&mut self, sample_rate: i32) {
+        FaustDsp::init(self, sample_rate);
+    }
+    fn build_user_interface(&self, ui_interface: &mut dyn UI<D::T>) {
+        FaustDsp::build_user_interface(self, ui_interface);
+    }
+    fn get_param(&self, param: ParamIndex) -> Option<D::T> {
+        FaustDsp::get_param(self, param)
+    }
+    fn set_param(&mut self, param: ParamIndex, value: D::T) {
+        FaustDsp::set_param(self, param, value);
+    }
+    fn compute(&mut self, count: i32, inputs: &[&[D::T]], outputs: &mut [&mut [D::T]]) {
+        FaustDsp::compute(self, count, inputs, outputs);
+    }
+}
+
+/// Creates instances of a dsp and runs its class-level initialization.
+///
+/// Object safe counterpart of the `Self: Sized` methods of [`FaustDsp`].
+pub trait DspFactory<T = f32> {
+    /// Initializes the tables shared by all instances.
+    fn class_init(&self, sample_rate: i32);
+    fn create(&self) -> Box<dyn DynFaustDsp<T> + Send>;
+    fn build_user_interface_static(&self, ui_interface: &mut dyn UI<T>);
+}
+
+/// The [`DspFactory`] of a [`FaustDsp`] type.
+pub struct FaustDspFactory<D>(PhantomData<fn() -> D>);
+
+impl<D> FaustDspFactory<D> {
+    #[must_use]
+    pub const fn new() -> Self {
+        Self(PhantomData)
+    }
+}
+
+impl<D> Default for FaustDspFactory<D> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<D> Clone for FaustDspFactory<D> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<D> Copy for FaustDspFactory<D> {}
+
+impl<D> fmt::Debug for FaustDspFactory<D> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(std::any::type_name::<Self>())
+    }
+}
+
+impl<D> DspFactory<D::T> for FaustDspFactory<D>
+where
+    D: FaustDsp + Send + 'static,
+{
+    fn class_init(&self, sample_rate: i32) {
+        D::class_init(sample_rate);
+    }
+    fn create(&self) -> Box<dyn DynFaustDsp<D::T> + Send> {
+        Box::new(D::new())
+    }
+    fn build_user_interface_static(&self, ui_interface: &mut dyn UI<D::T>) {
+        D::build_user_interface_static(ui_interface);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{DspFactory, DynFaustDsp, FaustDspFactory, ABI_VERSION, ABI_VERSION_NUL};
+    use crate::{
+        collect::{LayoutTree, ParamList},
+        test_dsp::Stereo,
+        InvalidParamIndex, ParamIndex,
+    };
+
+    #[test]
+    fn factory_creates_initialized_instances() {
+        let factory = FaustDspFactory::<Stereo>::new();
+        let factories: Vec<Box<dyn DspFactory>> = vec![Box::new(factory), Box::new(factory)];
+        let mut dsps: Vec<_> = factories
+            .iter()
+            .map(|factory| {
+                factory.class_init(48000);
+                factory.create()
+            })
+            .collect();
+        for dsp in &mut dsps {
+            dsp.init(48000);
+            assert_eq!(dsp.get_sample_rate(), 48000);
+            assert_eq!(dsp.get_param(ParamIndex(0)), Some(1.0));
+        }
+        let mut params = LayoutTree::new();
+        factory.build_user_interface_static(&mut params);
+        assert_eq!(ParamList::from(params), ParamList::from_dsp(&*dsps[0]));
+    }
+
+    #[test]
+    fn forwards_to_the_dsp() {
+        let mut dsp: Box<dyn DynFaustDsp> = Box::new(Stereo::default());
+        dsp.set_param(ParamIndex(0), 0.5);
+        assert_eq!(dsp.try_get_param(ParamIndex(0)), Ok(0.5));
+        assert_eq!(
+            dsp.try_set_param(ParamIndex(3), 1.0),
+            Err(InvalidParamIndex(ParamIndex(3)))
+        );
+        let mut left = [0.0; 2];
+        let mut right = [0.0; 2];
+        dsp.compute(2, &[&[1.0, 2.0], &[3.0, 4.0]], &mut [&mut left, &mut right]);
+        assert_eq!((left, right), ([0.5, 1.0], [1.5, 2.0]));
+    }
+
+    #[test]
+    fn abi_version_names_the_crate_and_the_compiler() {
+        assert!(ABI_VERSION.starts_with(concat!(env!("CARGO_PKG_VERSION"), " rustc ")));
+        assert_eq!(ABI_VERSION_NUL.strip_suffix('\0'), Some(ABI_VERSION));
+    }
+}
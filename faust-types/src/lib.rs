@@ -1,9 +1,127 @@
+pub mod buffer;
+pub mod collect;
+pub mod dynamic;
+pub mod scale;
+#[cfg(test)]
+mod test_dsp;
+
+pub use scale::{ParamRange, Scale};
+
+use std::{convert::TryFrom, error::Error, fmt, hash, marker::PhantomData};
+
+/// The version of this crate, for dependencies of generated crates.
+pub const VERSION: &str = env!("CARGO_PKG_VERSION");
+
 pub type F32 = f32;
 pub type F64 = f64;
 
-#[derive(Copy, Clone, Debug)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
 pub struct ParamIndex(pub i32);
 
+/// A [`ParamIndex`] declared by the dsp `D`, so it cannot be used with another dsp.
+///
+/// Obtained from the generated `param_index()` of the `UIActive` and `UIPassive` enums,
+/// or checked against `build_user_interface` with [`TypedParamIndex::new`].
+/// `ParamIndex` itself stays untyped, as faust generates it for any dsp.
+pub struct TypedParamIndex<D: ?Sized> {
+    index: ParamIndex,
+    dsp: PhantomData<fn() -> D>,
+}
+
+impl<D: ?Sized> TypedParamIndex<D> {
+    /// For generated code, which knows the indices of its dsp.
+    ///
+    /// # Safety
+    /// `D` must declare a widget with this index in `build_user_interface`.
+    /// Code using a `TypedParamIndex` may rely on it, e.g. to index without bounds checks.
+    #[doc(hidden)]
+    #[must_use]
+    pub const unsafe fn new_unchecked(index: ParamIndex) -> Self {
+        Self {
+            index,
+            dsp: PhantomData,
+        }
+    }
+
+    #[must_use]
+    pub const fn index(self) -> ParamIndex {
+        self.index
+    }
+}
+
+impl<D: FaustDsp> TypedParamIndex<D> {
+    /// Checks that `D` declares a widget with this index in `build_user_interface`.
+    ///
+    /// # Errors
+    /// If `D` has no widget with this index.
+    pub fn new(index: ParamIndex) -> Result<Self, InvalidParamIndex> {
+        let params = collect::ParamList::from_static::<D>();
+        if params.get(index).is_some() {
+            // SAFETY: checked above.
+            Ok(unsafe { Self::new_unchecked(index) })
+        } else {
+            Err(InvalidParamIndex(index))
+        }
+    }
+
+    pub fn set(self, dsp: &mut D, value: D::T) {
+        dsp.set_param(self.index, value);
+    }
+
+    /// The value of the parameter, `None` for widgets without a value like soundfiles.
+    pub fn get(self, dsp: &D) -> Option<D::T> {
+        dsp.get_param(self.index)
+    }
+}
+
+impl<D: ?Sized> Clone for TypedParamIndex<D> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<D: ?Sized> Copy for TypedParamIndex<D> {}
+
+impl<D: ?Sized> PartialEq for TypedParamIndex<D> {
+    fn eq(&self, other: &Self) -> bool {
+        self.index == other.index
+    }
+}
+
+impl<D: ?Sized> Eq for TypedParamIndex<D> {}
+
+impl<D: ?Sized> hash::Hash for TypedParamIndex<D> {
+    fn hash<H: hash::Hasher>(&self, state: &mut H) {
+        self.index.hash(state);
+    }
+}
+
+impl<D: ?Sized> fmt::Debug for TypedParamIndex<D> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("TypedParamIndex")
+            .field(&self.index.0)
+            .finish()
+    }
+}
+
+impl<D: ?Sized> From<TypedParamIndex<D>> for ParamIndex {
+    fn from(param: TypedParamIndex<D>) -> Self {
+        param.index
+    }
+}
+
+/// An index without a parameter in the dsp.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct InvalidParamIndex(pub ParamIndex);
+
+impl fmt::Display for InvalidParamIndex {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "no parameter with index {}", self.0 .0)
+    }
+}
+
+impl Error for InvalidParamIndex {}
+
 #[allow(dead_code)]
 #[allow(non_snake_case)]
 pub struct Soundfile<'a> {
@@ -14,6 +132,7 @@ pub struct Soundfile<'a> {
     fChannels: i32,
 }
 
+/// Interface of the dsp structs generated by faust.
 pub trait FaustDsp {
     type T;
 
@@ -39,6 +158,156 @@ pub trait FaustDsp {
     fn get_param(&self, param: ParamIndex) -> Option<Self::T>;
     fn set_param(&mut self, param: ParamIndex, value: Self::T);
     fn compute(&mut self, count: i32, inputs: &[&[Self::T]], outputs: &mut [&mut [Self::T]]);
+
+    /// [`set_param`](Self::set_param) reporting indices the dsp has no value for,
+    /// instead of ignoring them.
+    ///
+    /// # Errors
+    /// If [`get_param`](Self::get_param) returns `None` for the index.
+    fn try_set_param(
+        &mut self,
+        param: ParamIndex,
+        value: Self::T,
+    ) -> Result<(), InvalidParamIndex> {
+        self.try_get_param(param)?;
+        self.set_param(param, value);
+        Ok(())
+    }
+
+    /// [`get_param`](Self::get_param) reporting indices the dsp has no value for.
+    ///
+    /// # Errors
+    /// If [`get_param`](Self::get_param) returns `None` for the index.
+    fn try_get_param(&self, param: ParamIndex) -> Result<Self::T, InvalidParamIndex> {
+        self.get_param(param).ok_or(InvalidParamIndex(param))
+    }
+
+    /// Checked [`compute`](Self::compute), the number of frames is the length of the buffers.
+    ///
+    /// Blocks without frames are skipped.
+    ///
+    /// # Errors
+    /// See [`frame_count`].
+    fn process(
+        &mut self,
+        inputs: &[&[Self::T]],
+        outputs: &mut [&mut [Self::T]],
+    ) -> Result<(), ComputeError> {
+        let count = frame_count(
+            self.get_num_inputs(),
+            self.get_num_outputs(),
+            inputs,
+            outputs,
+        )?;
+        if count > 0 {
+            self.compute(count, inputs, outputs);
+        }
+        Ok(())
+    }
+}
+
+/// Channel and widget counts of a dsp known at compile time, for fixed size buffers.
+///
+/// Implemented by faust-build next to the `FaustDsp` implementation.
+/// A trait of its own, so `FaustDsp` stays usable as a trait object.
+pub trait FaustDspConsts: FaustDsp {
+    /// Number of input channels.
+    const INPUTS: usize;
+    /// Number of output channels.
+    const OUTPUTS: usize;
+    /// Number of active widgets (buttons, checkboxes, sliders and numeric entries).
+    const ACTIVES: usize;
+    /// Number of passive widgets (bargraphs).
+    const PASSIVES: usize;
+}
+
+/// Buffers rejected by [`FaustDsp::process`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ComputeError {
+    /// The number of input buffers differs from the inputs of the dsp.
+    Inputs { expected: usize, found: usize },
+    /// The number of output buffers differs from the outputs of the dsp.
+    Outputs { expected: usize, found: usize },
+    /// A buffer differs in length from the first one.
+    FrameCount { expected: usize, found: usize },
+    /// More frames than `compute` takes at once.
+    TooManyFrames(usize),
+    /// More frames than the preallocated buffers of an adapter in [`buffer`] hold.
+    BlockTooLong { max: usize, found: usize },
+    /// An interleaved buffer ending in the middle of a frame.
+    PartialFrame { len: usize, channels: usize },
+}
+
+impl fmt::Display for ComputeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Inputs { expected, found } => {
+                write!(f, "expected {} input buffers, found {}", expected, found)
+            }
+            Self::Outputs { expected, found } => {
+                write!(f, "expected {} output buffers, found {}", expected, found)
+            }
+            Self::FrameCount { expected, found } => write!(
+                f,
+                "buffers differ in length: expected {} frames, found {}",
+                expected, found
+            ),
+            Self::TooManyFrames(frames) => write!(f, "{} frames exceed i32::MAX", frames),
+            Self::BlockTooLong { max, found } => write!(
+                f,
+                "block of {} frames exceeds the {} preallocated frames",
+                found, max
+            ),
+            Self::PartialFrame { len, channels } => write!(
+                f,
+                "{} samples are no whole number of frames of {} channels",
+                len, channels
+            ),
+        }
+    }
+}
+
+impl Error for ComputeError {}
+
+/// Checks the buffers against the channel counts of a dsp and returns the number of frames.
+///
+/// All buffers must have the same length. Without any buffer the count is 0.
+///
+/// # Errors
+/// If the number of buffers differs from the number of channels,
+/// if the buffers differ in length or if they are longer than `i32::MAX`.
+pub fn frame_count<T>(
+    num_inputs: i32,
+    num_outputs: i32,
+    inputs: &[&[T]],
+    outputs: &[&mut [T]],
+) -> Result<i32, ComputeError> {
+    let expected = usize::try_from(num_inputs).unwrap_or(0);
+    if inputs.len() != expected {
+        return Err(ComputeError::Inputs {
+            expected,
+            found: inputs.len(),
+        });
+    }
+    let expected = usize::try_from(num_outputs).unwrap_or(0);
+    if outputs.len() != expected {
+        return Err(ComputeError::Outputs {
+            expected,
+            found: outputs.len(),
+        });
+    }
+    let mut lengths = inputs
+        .iter()
+        .map(|input| input.len())
+        .chain(outputs.iter().map(|output| output.len()));
+    let frames = lengths.next().unwrap_or(0);
+    if let Some(found) = lengths.find(|len| *len != frames) {
+        return Err(ComputeError::FrameCount {
+            expected: frames,
+            found,
+        });
+    }
+    i32::try_from(frames).map_err(|_| ComputeError::TooManyFrames(frames))
 }
 
 pub trait Meta {
@@ -46,16 +315,21 @@ pub trait Meta {
     fn declare(&mut self, key: &str, value: &str);
 }
 
+/// Visitor of the user interface declared by a dsp in `build_user_interface`.
+///
+/// All methods do nothing by default, implement the ones of interest.
+/// See [`collect`] for collectors of all widgets.
+#[allow(unused_variables)]
 pub trait UI<T> {
     // -- widget's layouts
-    fn open_tab_box(&mut self, label: &str);
-    fn open_horizontal_box(&mut self, label: &str);
-    fn open_vertical_box(&mut self, label: &str);
-    fn close_box(&mut self);
+    fn open_tab_box(&mut self, label: &str) {}
+    fn open_horizontal_box(&mut self, label: &str) {}
+    fn open_vertical_box(&mut self, label: &str) {}
+    fn close_box(&mut self) {}
 
     // -- active widgets
-    fn add_button(&mut self, label: &str, param: ParamIndex);
-    fn add_check_button(&mut self, label: &str, param: ParamIndex);
+    fn add_button(&mut self, label: &str, param: ParamIndex) {}
+    fn add_check_button(&mut self, label: &str, param: ParamIndex) {}
     fn add_vertical_slider(
         &mut self,
         label: &str,
@@ -64,7 +338,8 @@ pub trait UI<T> {
         min: T,
         max: T,
         step: T,
-    );
+    ) {
+    }
     fn add_horizontal_slider(
         &mut self,
         label: &str,
@@ -73,15 +348,16 @@ pub trait UI<T> {
         min: T,
         max: T,
         step: T,
-    );
-    fn add_num_entry(&mut self, label: &str, param: ParamIndex, init: T, min: T, max: T, step: T);
+    ) {
+    }
+    fn add_num_entry(&mut self, label: &str, param: ParamIndex, init: T, min: T, max: T, step: T) {}
 
     // -- passive widgets
-    fn add_horizontal_bargraph(&mut self, label: &str, param: ParamIndex, min: T, max: T);
-    fn add_vertical_bargraph(&mut self, label: &str, param: ParamIndex, min: T, max: T);
+    fn add_horizontal_bargraph(&mut self, label: &str, param: ParamIndex, min: T, max: T) {}
+    fn add_vertical_bargraph(&mut self, label: &str, param: ParamIndex, min: T, max: T) {}
 
     // -- metadata declarations
-    fn declare(&mut self, param: Option<ParamIndex>, key: &str, value: &str);
+    fn declare(&mut self, param: Option<ParamIndex>, key: &str, value: &str) {}
 }
 
 // traits for generated code
@@ -99,3 +375,113 @@ pub trait UIGet<D> {
     fn get_value(&self, dsp: &D) -> Self::F;
     fn get_enum(&self, dsp: &D) -> Self::E;
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        frame_count, ComputeError, FaustDsp, FaustDspConsts, InvalidParamIndex, ParamIndex,
+        TypedParamIndex,
+    };
+    use crate::test_dsp::Stereo;
+    use std::collections::HashSet;
+
+    #[test]
+    fn counts_the_frames_of_the_buffers() {
+        let mut outputs = [0.0; 3];
+        assert_eq!(frame_count(1, 1, &[&[0.0; 3]], &[&mut outputs]), Ok(3));
+        assert_eq!(frame_count::<f32>(0, 0, &[], &[]), Ok(0));
+        assert_eq!(frame_count::<f32>(-1, 0, &[], &[]), Ok(0));
+    }
+
+    #[test]
+    fn rejects_buffers_not_matching_the_dsp() {
+        let mut outputs = [0.0; 3];
+        assert_eq!(
+            frame_count(2, 1, &[&[0.0; 3]], &[&mut outputs]),
+            Err(ComputeError::Inputs {
+                expected: 2,
+                found: 1
+            })
+        );
+        assert_eq!(
+            frame_count(1, 0, &[&[0.0; 3]], &[&mut outputs]),
+            Err(ComputeError::Outputs {
+                expected: 0,
+                found: 1
+            })
+        );
+        assert_eq!(
+            frame_count(1, 1, &[&[0.0; 4]], &[&mut outputs]),
+            Err(ComputeError::FrameCount {
+                expected: 4,
+                found: 3
+            })
+        );
+    }
+
+    #[test]
+    fn counts_channels_at_compile_time() {
+        let inputs = [[0.0; 4]; Stereo::INPUTS];
+        let mut outputs = [[0.0; 4]; Stereo::OUTPUTS];
+        let mut dsp: Box<dyn FaustDsp<T = f32>> = Box::new(Stereo::new());
+        let inputs: Vec<&[f32]> = inputs.iter().map(|input| &input[..]).collect();
+        let mut outputs: Vec<&mut [f32]> =
+            outputs.iter_mut().map(|output| &mut output[..]).collect();
+        assert_eq!(dsp.process(&inputs, &mut outputs), Ok(()));
+        assert_eq!((Stereo::ACTIVES, Stereo::PASSIVES), (2, 1));
+    }
+
+    #[test]
+    fn processes_checked_buffers() {
+        let mut dsp = Stereo::new();
+        dsp.init(48000);
+        let mut left = [0.0; 2];
+        let mut right = [0.0; 2];
+        assert_eq!(
+            dsp.process(&[&[1.0, 2.0], &[3.0, 4.0]], &mut [&mut left, &mut right]),
+            Ok(())
+        );
+        assert_eq!((left, right), ([1.0, 2.0], [3.0, 4.0]));
+        assert_eq!(dsp.process(&[&[], &[]], &mut [&mut [], &mut []]), Ok(()));
+        assert!(dsp
+            .process(&[&[5.0; 2]], &mut [&mut left, &mut right])
+            .is_err());
+        assert_eq!(left, [1.0, 2.0]);
+    }
+
+    #[test]
+    fn checks_typed_indices_against_the_widgets() {
+        assert_eq!(
+            TypedParamIndex::<Stereo>::new(ParamIndex(3)),
+            Err(InvalidParamIndex(ParamIndex(3)))
+        );
+        let gain = TypedParamIndex::<Stereo>::new(ParamIndex(0)).expect("declared index");
+        let mut dsp = Stereo::new();
+        dsp.init(48000);
+        gain.set(&mut dsp, 0.25);
+        assert_eq!(gain.get(&dsp), Some(0.25));
+        assert_eq!(ParamIndex::from(gain), gain.index());
+        assert_eq!(format!("{:?}", gain), "TypedParamIndex(0)");
+    }
+
+    #[test]
+    fn compares_typed_indices_by_index() {
+        let indices: HashSet<_> = [0, 1, 0]
+            .iter()
+            .map(|index| {
+                TypedParamIndex::<Stereo>::new(ParamIndex(*index)).expect("declared index")
+            })
+            .collect();
+        assert_eq!(indices.len(), 2);
+        assert!(indices.contains(&TypedParamIndex::new(ParamIndex(1)).expect("declared index")));
+    }
+
+    #[test]
+    fn reports_invalid_indices() {
+        let mut dsp = Stereo::new();
+        assert_eq!(dsp.try_set_param(ParamIndex(1), 0.5), Ok(()));
+        assert_eq!(dsp.try_get_param(ParamIndex(1)), Ok(0.5));
+        let error = dsp.try_set_param(ParamIndex(-1), 0.5).unwrap_err();
+        assert_eq!(error.to_string(), "no parameter with index -1");
+    }
+}
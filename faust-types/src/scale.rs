@@ -0,0 +1,243 @@
+//! Conversion between plain values of a widget, normalized values in `0..=1` and text.
+//!
+//! The mapping follows the `[scale:log]` and `[scale:exp]` metadata like faust's `ValueConverter`.
+//!
+//! ```
+//! # use faust_types::{ParamRange, Scale};
+//! let range = ParamRange::new(20.0, 20000.0, 1.0, Scale::Log);
+//! assert_eq!(range.from_normalized(0.5), 632.0);
+//! assert_eq!(range.to_text(632.0, "Hz"), "632 Hz");
+//! ```
+
+/// The mapping of the normalized value to the range of a widget.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum Scale {
+    #[default]
+    Linear,
+    /// `[scale:log]`, more resolution near the minimum.
+    Log,
+    /// `[scale:exp]`, more resolution near the maximum.
+    Exp,
+}
+
+impl Scale {
+    /// The scale of the value of the `scale` metadata, linear for unknown values.
+    #[must_use]
+    pub fn from_metadata(value: &str) -> Self {
+        match value.trim() {
+            "log" => Self::Log,
+            "exp" => Self::Exp,
+            _ => Self::Linear,
+        }
+    }
+}
+
+/// The declared range, step and scale of a widget.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ParamRange {
+    pub min: f64,
+    pub max: f64,
+    /// 0 for widgets without a step like bargraphs.
+    pub step: f64,
+    pub scale: Scale,
+}
+
+impl ParamRange {
+    #[must_use]
+    pub const fn new(min: f64, max: f64, step: f64, scale: Scale) -> Self {
+        Self {
+            min,
+            max,
+            step,
+            scale,
+        }
+    }
+
+    /// The range of a `f32` dsp, with the values as declared, like `0.1` instead of `0.100000001`.
+    #[must_use]
+    pub fn from_f32(min: f32, max: f32, step: f32, scale: Scale) -> Self {
+        Self::new(decimal(min), decimal(max), decimal(step), scale)
+    }
+
+    /// `value` clamped to the range.
+    #[must_use]
+    pub fn clamp(&self, value: f64) -> f64 {
+        value.max(self.min).min(self.max)
+    }
+
+    /// The value nearest to `value` on the steps from the minimum, within the range.
+    #[must_use]
+    pub fn quantize(&self, value: f64) -> f64 {
+        if self.step > 0.0 {
+            self.clamp(
+                ((value - self.min) / self.step)
+                    .round()
+                    .mul_add(self.step, self.min),
+            )
+        } else {
+            self.clamp(value)
+        }
+    }
+
+    /// The position of `value` in the range, from 0 at the minimum to 1 at the maximum.
+    #[must_use]
+    pub fn to_normalized(&self, value: f64) -> f64 {
+        let (low, high, value) = match self.scale {
+            Scale::Linear => (self.min, self.max, self.clamp(value)),
+            Scale::Log => (log(self.min), log(self.max), log(self.clamp(value))),
+            Scale::Exp => (self.min.exp(), self.max.exp(), self.clamp(value).exp()),
+        };
+        if high == low {
+            0.0
+        } else {
+            ((value - low) / (high - low)).clamp(0.0, 1.0)
+        }
+    }
+
+    /// The value at the position `normalized` in the range, quantized to the step.
+    #[must_use]
+    pub fn from_normalized(&self, normalized: f64) -> f64 {
+        let normalized = normalized.clamp(0.0, 1.0);
+        let value = match self.scale {
+            Scale::Linear => normalized.mul_add(self.max - self.min, self.min),
+            Scale::Log => {
+                let (low, high) = (log(self.min), log(self.max));
+                normalized.mul_add(high - low, low).exp()
+            }
+            Scale::Exp => {
+                let (low, high) = (self.min.exp(), self.max.exp());
+                normalized.mul_add(high - low, low).ln()
+            }
+        };
+        self.quantize(value)
+    }
+
+    /// The number of decimals of the step, 2 without a step.
+    #[must_use]
+    pub fn decimals(&self) -> usize {
+        if self.step <= 0.0 {
+            return 2;
+        }
+        (0..6)
+            .find(|decimals| {
+                let scaled = self.step * 10_f64.powi(*decimals);
+                (scaled - scaled.round()).abs() < 1e-6 * scaled.max(1.0)
+            })
+            .map_or(6, |decimals| decimals as usize)
+    }
+
+    /// `value` with the decimals of the step, followed by the unit if there is one.
+    #[must_use]
+    pub fn to_text(&self, value: f64, unit: &str) -> String {
+        let decimals = self.decimals();
+        if unit.is_empty() {
+            format!("{value:.decimals$}")
+        } else {
+            format!("{value:.decimals$} {unit}")
+        }
+    }
+
+    /// Parses text like the one of [`to_text`](Self::to_text), the unit is optional.
+    ///
+    /// The value is clamped to the range and quantized to the step.
+    #[must_use]
+    pub fn from_text(&self, text: &str, unit: &str) -> Option<f64> {
+        let text = text.trim();
+        let number = text.strip_suffix(unit).unwrap_or(text).trim();
+        let value: f64 = number.parse().ok()?;
+        value.is_finite().then(|| self.quantize(value))
+    }
+}
+
+/// The natural logarithm, limited to the smallest positive value like faust's `LogValueConverter`.
+fn log(value: f64) -> f64 {
+    value.max(f64::MIN_POSITIVE).ln()
+}
+
+/// The shortest decimal representation of `value`, like `0.1` for `0.1_f32`.
+fn decimal(value: f32) -> f64 {
+    value
+        .to_string()
+        .parse()
+        .unwrap_or_else(|_| f64::from(value))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ParamRange, Scale};
+
+    fn assert_close(found: f64, expected: f64) {
+        assert!(
+            (found - expected).abs() <= 1e-9 * expected.abs().max(1.0),
+            "{} != {}",
+            found,
+            expected
+        );
+    }
+
+    #[test]
+    fn round_trips_through_normalized_values() {
+        for scale in [Scale::Linear, Scale::Log, Scale::Exp] {
+            let range = ParamRange::new(0.5, 8.0, 0.0, scale);
+            for value in [0.5, 0.75, 1.0, 3.3, 7.9, 8.0] {
+                let normalized = range.to_normalized(value);
+                assert!((0.0..=1.0).contains(&normalized));
+                assert_close(range.from_normalized(normalized), value);
+            }
+        }
+    }
+
+    #[test]
+    fn maps_log_and_exp_ranges_like_faust() {
+        let log = ParamRange::new(1.0, 100.0, 0.0, Scale::Log);
+        assert_close(log.from_normalized(0.5), 10.0);
+        assert_close(log.to_normalized(10.0), 0.5);
+        let exp = ParamRange::new(0.0, 2.0, 0.0, Scale::Exp);
+        assert_close(exp.from_normalized(0.5), ((1.0 + 2_f64.exp()) / 2.0).ln());
+        assert!(exp.to_normalized(1.0) < 0.5);
+    }
+
+    #[test]
+    fn limits_log_ranges_reaching_zero_or_below() {
+        for min in [0.0, -1.0] {
+            let range = ParamRange::new(min, 1.0, 0.0, Scale::Log);
+            assert_close(range.to_normalized(min), 0.0);
+            assert_close(range.to_normalized(-0.5), 0.0);
+            assert_close(range.to_normalized(1.0), 1.0);
+            assert_close(range.from_normalized(1.0), 1.0);
+            let mut previous = min;
+            for step in 0..=10 {
+                let value = range.from_normalized(f64::from(step) / 10.0);
+                assert!(value.is_finite() && value >= previous, "{}", value);
+                previous = value;
+            }
+            assert_close(range.from_normalized(range.to_normalized(0.25)), 0.25);
+        }
+    }
+
+    #[test]
+    fn quantizes_to_the_step_from_the_minimum() {
+        let range = ParamRange::new(-1.0, 1.0, 0.3, Scale::Linear);
+        assert_close(range.quantize(0.1), 0.2);
+        assert_close(range.quantize(5.0), 1.0);
+        assert_close(range.from_normalized(2.0), 1.0);
+        let empty = ParamRange::new(1.0, 1.0, 0.0, Scale::Log);
+        assert_close(empty.to_normalized(1.0), 0.0);
+    }
+
+    #[test]
+    fn converts_text_with_the_decimals_of_the_step() {
+        let range = ParamRange::from_f32(0.0, 10.0, 0.1, Scale::Linear);
+        assert_eq!(range.decimals(), 1);
+        assert_eq!(range.to_text(2.26, "dB"), "2.3 dB");
+        assert_close(range.from_text(" 2.26 dB ", "dB").expect("number"), 2.3);
+        assert_eq!(range.from_text("12", "dB"), Some(10.0));
+        assert_eq!(range.from_text("inf", ""), None);
+        assert_eq!(
+            ParamRange::new(0.0, 1.0, 0.0, Scale::Linear).to_text(0.5, ""),
+            "0.50"
+        );
+        assert_eq!(Scale::from_metadata(" log"), Scale::Log);
+        assert_eq!(Scale::from_metadata("db"), Scale::Linear);
+    }
+}
@@ -0,0 +1,99 @@
+//! A dsp written like the ones generated by faust, for the tests.
+
+use crate::{FaustDsp, FaustDspConsts, Meta, ParamIndex, UI};
+
+/// Multiplies two inputs by `gain`, shows the last output sample in `level`.
+#[derive(Debug, Clone, Default)]
+pub struct Stereo {
+    sample_rate: i32,
+    gain: f32,
+    level: f32,
+    mute: f32,
+}
+
+impl FaustDsp for Stereo {
+    type T = f32;
+
+    fn new() -> Self {
+        Self::default()
+    }
+    fn metadata(&self, m: &mut dyn Meta) {
+        m.declare("name", "stereo");
+    }
+    fn get_sample_rate(&self) -> i32 {
+        self.sample_rate
+    }
+    fn get_num_inputs(&self) -> i32 {
+        2
+    }
+    fn get_num_outputs(&self) -> i32 {
+        2
+    }
+    fn class_init(_sample_rate: i32) {}
+    fn instance_reset_params(&mut self) {
+        self.gain = 1.0;
+        self.mute = 0.0;
+    }
+    fn instance_clear(&mut self) {
+        self.level = 0.0;
+    }
+    fn instance_constants(&mut self, sample_rate: i32) {
+        self.sample_rate = sample_rate;
+    }
+    fn instance_init(&mut self, sample_rate: i32) {
+        self.instance_constants(sample_rate);
+        self.instance_reset_params();
+        self.instance_clear();
+    }
+    fn init(&mut self, sample_rate: i32) {
+        Self::class_init(sample_rate);
+        self.instance_init(sample_rate);
+    }
+    fn build_user_interface(&self, ui_interface: &mut dyn UI<f32>) {
+        Self::build_user_interface_static(ui_interface);
+    }
+    fn build_user_interface_static(ui_interface: &mut dyn UI<f32>) {
+        ui_interface.open_vertical_box("stereo");
+        ui_interface.declare(None, "0", "");
+        ui_interface.open_horizontal_box("out");
+        ui_interface.declare(Some(ParamIndex(0)), "unit", "dB");
+        ui_interface.add_horizontal_slider("gain", ParamIndex(0), 1.0, 0.0, 2.0, 0.01);
+        ui_interface.add_vertical_bargraph("level", ParamIndex(1), 0.0, 2.0);
+        ui_interface.declare(Some(ParamIndex(1)), "style", "led");
+        ui_interface.close_box();
+        ui_interface.add_button("mute", ParamIndex(2));
+        ui_interface.close_box();
+    }
+    fn get_param(&self, param: ParamIndex) -> Option<f32> {
+        match param.0 {
+            0 => Some(self.gain),
+            1 => Some(self.level),
+            2 => Some(self.mute),
+            _ => None,
+        }
+    }
+    fn set_param(&mut self, param: ParamIndex, value: f32) {
+        match param.0 {
+            0 => self.gain = value,
+            1 => self.level = value,
+            2 => self.mute = value,
+            _ => {}
+        }
+    }
+    fn compute(&mut self, count: i32, inputs: &[&[f32]], outputs: &mut [&mut [f32]]) {
+        let gain = if self.mute == 0.0 { self.gain } else { 0.0 };
+        for (input, output) in inputs.iter().zip(outputs.iter_mut()) {
+            for (input, output) in input[..count as usize].iter().zip(&mut output[..]) {
+                *output = input * gain;
+                self.level = *output;
+            }
+        }
+    }
+}
+
+impl FaustDspConsts for Stereo {
+    const INPUTS: usize = 2;
+    const OUTPUTS: usize = 2;
+    const ACTIVES: usize = 2;
+    const PASSIVES: usize = 1;
+}
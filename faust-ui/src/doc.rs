@@ -0,0 +1,52 @@
+//! Doc comments from the metadata in the json file.
+
+use faust_json::{FaustJson, Meta};
+use proc_macro2::TokenStream;
+use quote::quote;
+
+/// A `#[doc]` attribute per line of `paragraphs`, empty paragraphs are left out.
+#[must_use]
+pub fn attributes<S: AsRef<str>>(paragraphs: &[S]) -> TokenStream {
+    let mut lines: Vec<String> = Vec::new();
+    for paragraph in paragraphs.iter().map(AsRef::as_ref) {
+        if paragraph.trim().is_empty() {
+            continue;
+        }
+        if !lines.is_empty() {
+            lines.push(String::new());
+        }
+        lines.extend(paragraph.lines().map(|line| format!(" {}", line.trim())));
+    }
+    quote! { #(#[doc = #lines])* }
+}
+
+fn meta_value<'a>(meta: &'a [Meta], key: &str) -> Option<&'a str> {
+    meta.iter()
+        .find(|meta| meta.key == key)
+        .map(|meta| meta.value.as_str())
+}
+
+/// The name, description, author, license and version of the dsp.
+#[must_use]
+pub fn dsp(dsp_json: &FaustJson) -> TokenStream {
+    let meta = |key| meta_value(&dsp_json.meta, key);
+    let details: Vec<String> = [
+        (
+            "Author",
+            dsp_json.author.as_deref().or_else(|| meta("author")),
+        ),
+        (
+            "License",
+            dsp_json.license.as_deref().or_else(|| meta("license")),
+        ),
+        ("Version", meta("version")),
+    ]
+    .into_iter()
+    .filter_map(|(name, value)| Some(format!("- {name}: {}", value?)))
+    .collect();
+    attributes(&[
+        format!("The `{}` dsp.", dsp_json.name),
+        meta("description").unwrap_or_default().to_owned(),
+        details.join("\n"),
+    ])
+}
@@ -1,6 +1,7 @@
-use faust_json::{FaustJson, LayoutItem};
-use heck::CamelCase;
-use proc_macro2::TokenStream;
+use crate::ident::{self, Names};
+use crate::{doc, ParamAccess, UiOptions};
+use faust_json::{FaustJson, LayoutItem, Meta};
+use proc_macro2::{Literal, TokenStream};
 use quote::{format_ident, quote, ToTokens};
 use std::vec;
 use syn::Ident;
@@ -10,6 +11,56 @@ const UIENUMVALUE: &str = "Value";
 const UIENUMACTIVE: &str = "Active";
 const UIENUMPASSIVE: &str = "Passive";
 
+/// Names used by the generated code besides the enums, not available for the choice enums.
+const RESERVED: &[&str] = &[
+    "Display",
+    "EnumCount",
+    "EnumDiscriminants",
+    "EnumIter",
+    "FaustDsp",
+    "FaustFloat",
+    "IntoStaticStr",
+    "ParamIndex",
+    "ParamRange",
+    "Params",
+    "Scale",
+    "TypedParamIndex",
+    "UIGet",
+    "UISelfSet",
+    "UISet",
+    "VariantArray",
+    "VariantNames",
+];
+
+/// Methods of the dsp struct, generated by faust, `FaustDsp` or faust-ui,
+/// not available for the accessors.
+const DSP_METHODS: &[&str] = &[
+    "build_user_interface",
+    "build_user_interface_static",
+    "class_init",
+    "clone",
+    "compute",
+    "default",
+    "default_boxed",
+    "get_num_inputs",
+    "get_num_outputs",
+    "get_param",
+    "get_sample_rate",
+    "init",
+    "instance_clear",
+    "instance_constants",
+    "instance_init",
+    "instance_reset_params",
+    "instance_reset_user_interface",
+    "metadata",
+    "new",
+    "process",
+    "set",
+    "set_param",
+    "try_get_param",
+    "try_set_param",
+];
+
 #[must_use]
 pub fn enum_active_value_ident() -> Ident {
     format_ident!("{UIENUMPREFIX}{UIENUMACTIVE}{UIENUMVALUE}")
@@ -30,42 +81,437 @@ pub fn enum_passive_discriminants_ident() -> Ident {
     format_ident!("{UIENUMPREFIX}{UIENUMPASSIVE}")
 }
 
+/// The widgets with a value, soundfiles have none.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Kind {
+    Button,
+    CheckBox,
+    VSlider,
+    HSlider,
+    NEntry,
+    VBarGraph,
+    HBarGraph,
+}
+
+impl Kind {
+    /// Bargraphs are passive, their value is set by the dsp.
+    const fn is_active(self) -> bool {
+        !matches!(self, Self::VBarGraph | Self::HBarGraph)
+    }
+
+    const fn description(self) -> &'static str {
+        match self {
+            Self::Button => "Button",
+            Self::CheckBox => "Checkbox",
+            Self::VSlider => "Vertical slider",
+            Self::HSlider => "Horizontal slider",
+            Self::NEntry => "Number entry",
+            Self::VBarGraph => "Vertical bargraph",
+            Self::HBarGraph => "Horizontal bargraph",
+        }
+    }
+}
+
 struct ParamInfo {
-    is_active: bool,
+    kind: Kind,
+    /// The shortname faust gave the widget.
+    name: String,
+    /// The variant of the widget in the enums, unique across both of them.
     shortname: Ident,
     varname: Ident,
+    /// Position in `build_user_interface`, which is the `ParamIndex` of the widget.
+    index: i32,
+    label: String,
+    address: String,
+    /// Declared init value, 0 for buttons and checkboxes, the minimum for bargraphs.
+    init: f32,
+    /// Declared minimum, maximum and step, buttons and checkboxes range from 0 to 1.
+    range: [f32; 3],
+    /// Metadata in declaration order.
+    meta: Vec<(String, String)>,
+    /// Value of the `scale` metadata.
+    scale: Option<String>,
+    /// Value of the `unit` metadata.
+    unit: String,
+    /// Labels and values of the `style:menu` or `style:radio` metadata.
+    choices: Vec<(String, f32)>,
 }
 
 impl ParamInfo {
-    fn active(shortname: &str, varname: &str) -> Vec<Self> {
+    fn new(
+        kind: Kind,
+        label: &str,
+        shortname: &str,
+        address: &str,
+        varname: &str,
+        [init, min, max, step]: [f32; 4],
+        meta: &[Meta],
+    ) -> Vec<Self> {
+        let meta_value = |key: &str| {
+            meta.iter()
+                .find(|meta| meta.key == key)
+                .map(|meta| meta.value.clone())
+        };
         vec![Self {
-            is_active: true,
-            shortname: format_ident!("{shortname}"),
+            kind,
+            name: shortname.to_owned(),
+            shortname: format_ident!("{}", ident::camel(shortname)),
             varname: format_ident!("{varname}"),
+            index: 0,
+            label: label.to_owned(),
+            address: address.to_owned(),
+            init,
+            range: [min, max, step],
+            meta: meta
+                .iter()
+                .map(|meta| (meta.key.clone(), meta.value.clone()))
+                .collect(),
+            scale: meta_value("scale"),
+            unit: meta_value("unit").unwrap_or_default(),
+            choices: meta_value("style")
+                .filter(|_| kind.is_active())
+                .and_then(|style| parse_choices(&style))
+                .unwrap_or_default(),
         }]
     }
-    fn passive(shortname: &str, varname: &str) -> Vec<Self> {
-        vec![Self {
-            is_active: false,
-            shortname: format_ident!("{shortname}"),
-            varname: format_ident!("{varname}"),
-        }]
+
+    /// The `ParamRange` of the widget, with the values as declared.
+    fn param_range(&self) -> TokenStream {
+        let [min, max, step] = self.range.map(decimal);
+        let scale = match self.scale.as_deref().map(str::trim) {
+            Some("log") => quote! { Scale::Log },
+            Some("exp") => quote! { Scale::Exp },
+            _ => quote! { Scale::Linear },
+        };
+        quote! { ParamRange::new(#min, #max, #step, #scale) }
+    }
+
+    /// The variants of the choice enum.
+    fn choice_variants(&self) -> Vec<Ident> {
+        let mut names = Names::default();
+        self.choices
+            .iter()
+            .map(|(label, _)| names.camel(label))
+            .collect()
+    }
+
+    /// The address, tooltip and declared range of the widget.
+    fn doc(&self) -> TokenStream {
+        let tooltip = self
+            .meta
+            .iter()
+            .find(|(key, _)| key == "tooltip")
+            .map(|(_, value)| value.as_str());
+        doc::attributes(&[
+            &format!("{} `{}`.", self.kind.description(), self.address),
+            tooltip.unwrap_or_default(),
+            &self.range_doc(),
+        ])
+    }
+
+    /// The declared range with the unit, like ``"`-70` to `4` dB, step `0.1`, initially `0`."``
+    fn range_doc(&self) -> String {
+        let init = shortest(self.init);
+        let [min, max, step] = self.range.map(shortest);
+        let unit = if self.unit.is_empty() {
+            String::new()
+        } else {
+            format!(" {}", self.unit)
+        };
+        match self.kind {
+            Kind::Button => "`1` while pressed, `0` otherwise.".to_owned(),
+            Kind::CheckBox => "`1` if checked, `0` otherwise.".to_owned(),
+            Kind::VSlider | Kind::HSlider | Kind::NEntry => {
+                format!("`{min}` to `{max}`{unit}, step `{step}`, initially `{init}`.")
+            }
+            Kind::VBarGraph | Kind::HBarGraph => format!("`{min}` to `{max}`{unit}."),
+        }
+    }
+
+    /// The `collect::WidgetKind` of the widget.
+    fn widget_kind(&self) -> TokenStream {
+        let init = float(self.init);
+        let [min, max, step] = self.range.map(float);
+        match self.kind {
+            Kind::Button => quote! { collect::WidgetKind::Button },
+            Kind::CheckBox => quote! { collect::WidgetKind::CheckButton },
+            Kind::VSlider => quote! {
+                collect::WidgetKind::VerticalSlider { init: #init, min: #min, max: #max, step: #step }
+            },
+            Kind::HSlider => quote! {
+                collect::WidgetKind::HorizontalSlider { init: #init, min: #min, max: #max, step: #step }
+            },
+            Kind::NEntry => quote! {
+                collect::WidgetKind::NumEntry { init: #init, min: #min, max: #max, step: #step }
+            },
+            Kind::VBarGraph => {
+                quote! { collect::WidgetKind::VerticalBargraph { min: #min, max: #max } }
+            }
+            Kind::HBarGraph => {
+                quote! { collect::WidgetKind::HorizontalBargraph { min: #min, max: #max } }
+            }
+        }
     }
 }
+
+/// `value` as `f64` literal with its shortest decimal representation, like `0.1` for `0.1_f32`.
+fn decimal(value: f32) -> Literal {
+    Literal::f64_suffixed(shortest(value))
+}
+
+/// `value` as unsuffixed literal, inferred as `FaustFloat`.
+fn float(value: f32) -> Literal {
+    Literal::f64_unsuffixed(shortest(value))
+}
+
+fn shortest(value: f32) -> f64 {
+    value
+        .to_string()
+        .parse()
+        .unwrap_or_else(|_| f64::from(value))
+}
+
+/// The labels and values of a `menu{'Sine':0;'Saw':1}` or `radio{..}` style,
+/// `None` for other styles and malformed lists.
+fn parse_choices(style: &str) -> Option<Vec<(String, f32)>> {
+    let style = style.trim();
+    let list = style
+        .strip_prefix("menu")
+        .or_else(|| style.strip_prefix("radio"))?
+        .trim()
+        .strip_prefix('{')?
+        .strip_suffix('}')?;
+    let choices = list
+        .split(';')
+        .filter(|item| !item.trim().is_empty())
+        .map(|item| {
+            let (label, value) = item.trim().strip_prefix('\'')?.rsplit_once('\'')?;
+            let value = value.trim().strip_prefix(':')?.trim().parse().ok()?;
+            Some((label.to_owned(), value))
+        })
+        .collect::<Option<Vec<_>>>()?;
+    (!choices.is_empty()).then_some(choices)
+}
+
 trait GetParmInfo {
     fn get_param_info(&self) -> Vec<ParamInfo>;
 }
 
 impl GetParmInfo for FaustJson {
     fn get_param_info(&self) -> Vec<ParamInfo> {
-        self.ui
+        let mut infos: Vec<ParamInfo> = self
+            .ui
             .iter()
             .flat_map(GetParmInfo::get_param_info)
+            .collect();
+        let mut indices = Vec::with_capacity(infos.len());
+        widget_indices(&self.ui, &mut 0, &mut indices);
+        let mut names = Names::default();
+        for (info, index) in infos.iter_mut().zip(indices) {
+            info.index = index;
+            info.shortname = names.camel(&info.name);
+        }
+        infos
+    }
+}
+
+/// The `ParamIndex` of every widget with a value in declaration order.
+///
+/// Soundfiles have no value but take an index, like the zones of the C backend wrapper.
+fn widget_indices(items: &[LayoutItem], next: &mut i32, indices: &mut Vec<i32>) {
+    for item in items {
+        match item {
+            LayoutItem::HGroup { items, .. }
+            | LayoutItem::VGroup { items, .. }
+            | LayoutItem::TGroup { items, .. } => widget_indices(items, next, indices),
+            LayoutItem::Soundfile { .. } => *next += 1,
+            _ => {
+                indices.push(*next);
+                *next += 1;
+            }
+        }
+    }
+}
+
+impl ParamInfo {
+    fn set(&self, access: ParamAccess, value: &TokenStream) -> TokenStream {
+        match access {
+            ParamAccess::Field => {
+                let varname = &self.varname;
+                quote! { dsp.#varname = #value }
+            }
+            ParamAccess::Index => {
+                let index = self.index;
+                quote! { FaustDsp::set_param(dsp, ParamIndex(#index), #value) }
+            }
+        }
+    }
+
+    fn get(&self, access: ParamAccess) -> TokenStream {
+        match access {
+            ParamAccess::Field => {
+                let varname = &self.varname;
+                quote! { dsp.#varname }
+            }
+            ParamAccess::Index => {
+                let index = self.index;
+                quote! { FaustDsp::get_param(dsp, ParamIndex(#index)).unwrap_or_default() }
+            }
+        }
+    }
+}
+
+/// The `param_index()` match arms of a discriminant enum.
+fn param_index_matches(infos: &[&ParamInfo], enum_name: &Ident) -> Vec<TokenStream> {
+    infos
+        .iter()
+        .map(|param_info| {
+            let shortname = &param_info.shortname;
+            let index = param_info.index;
+            quote! { #enum_name::#shortname => TypedParamIndex::new_unchecked(ParamIndex(#index)) }
+        })
+        .collect()
+}
+
+/// The `const fn`s of a discriminant enum returning the declaration of the widget.
+fn declared_methods(infos: &[&ParamInfo], enum_name: &Ident) -> TokenStream {
+    let arms = |value: &dyn Fn(&ParamInfo) -> TokenStream| -> Vec<TokenStream> {
+        infos
+            .iter()
+            .map(|param_info| {
+                let shortname = &param_info.shortname;
+                let value = value(param_info);
+                quote! { #enum_name::#shortname => #value }
+            })
             .collect()
+    };
+    let matches_min = arms(&|param_info| float(param_info.range[0]).to_token_stream());
+    let matches_max = arms(&|param_info| float(param_info.range[1]).to_token_stream());
+    let matches_init = arms(&|param_info| float(param_info.init).to_token_stream());
+    let matches_step = arms(&|param_info| float(param_info.range[2]).to_token_stream());
+    let matches_label = arms(&|param_info| param_info.label.to_token_stream());
+    let matches_address = arms(&|param_info| param_info.address.to_token_stream());
+    let matches_kind = arms(&ParamInfo::widget_kind);
+    let matches_metadata = arms(&|param_info| {
+        let (keys, values): (Vec<&String>, Vec<&String>) = param_info
+            .meta
+            .iter()
+            .map(|(key, value)| (key, value))
+            .unzip();
+        quote! { &[#((#keys, #values)),*] }
+    });
+    quote! {
+        pub const fn min(&self) -> FaustFloat {
+            match self {
+                #(#matches_min ),*
+            }
+        }
+        pub const fn max(&self) -> FaustFloat {
+            match self {
+                #(#matches_max ),*
+            }
+        }
+        pub const fn init(&self) -> FaustFloat {
+            match self {
+                #(#matches_init ),*
+            }
+        }
+        pub const fn step(&self) -> FaustFloat {
+            match self {
+                #(#matches_step ),*
+            }
+        }
+        pub const fn label(&self) -> &'static str {
+            match self {
+                #(#matches_label ),*
+            }
+        }
+        pub const fn address(&self) -> &'static str {
+            match self {
+                #(#matches_address ),*
+            }
+        }
+        pub const fn kind(&self) -> collect::WidgetKind<FaustFloat> {
+            match self {
+                #(#matches_kind ),*
+            }
+        }
+        pub const fn metadata(&self) -> &'static [(&'static str, &'static str)] {
+            match self {
+                #(#matches_metadata ),*
+            }
+        }
+    }
+}
+
+/// The methods of a discriminant enum returning the range and unit,
+/// and converting between plain, normalized and text values.
+fn normalized_methods(
+    infos: &[&ParamInfo],
+    enum_name: &Ident,
+    dsp_name: &Ident,
+    access: ParamAccess,
+) -> TokenStream {
+    let matches_range: Vec<TokenStream> = infos
+        .iter()
+        .map(|param_info| {
+            let shortname = &param_info.shortname;
+            let range = param_info.param_range();
+            quote! { #enum_name::#shortname => #range }
+        })
+        .collect();
+    let matches_unit: Vec<TokenStream> = infos
+        .iter()
+        .map(|param_info| {
+            let shortname = &param_info.shortname;
+            let unit = &param_info.unit;
+            quote! { #enum_name::#shortname => #unit }
+        })
+        .collect();
+    let matches_get: Vec<TokenStream> = infos
+        .iter()
+        .map(|param_info| {
+            let shortname = &param_info.shortname;
+            let get = param_info.get(access);
+            quote! { #enum_name::#shortname => #get }
+        })
+        .collect();
+    quote! {
+        pub const fn range(&self) -> ParamRange {
+            match self {
+                #(#matches_range ),*
+            }
+        }
+        pub const fn unit(&self) -> &'static str {
+            match self {
+                #(#matches_unit ),*
+            }
+        }
+        pub fn to_normalized(&self, value: FaustFloat) -> FaustFloat {
+            self.range().to_normalized(value as f64) as FaustFloat
+        }
+        pub fn from_normalized(&self, normalized: FaustFloat) -> FaustFloat {
+            self.range().from_normalized(normalized as f64) as FaustFloat
+        }
+        pub fn get_normalized(&self, dsp: &#dsp_name) -> FaustFloat {
+            let value = match self {
+                #(#matches_get ),*
+            };
+            self.to_normalized(value)
+        }
+        pub fn to_text(&self, value: FaustFloat) -> String {
+            self.range().to_text(value as f64, self.unit())
+        }
+        pub fn from_text(&self, text: &str) -> Option<FaustFloat> {
+            self.range()
+                .from_text(text, self.unit())
+                .map(|value| value as FaustFloat)
+        }
     }
 }
 
 impl GetParmInfo for LayoutItem {
+    #[allow(clippy::too_many_lines)]
     fn get_param_info(&self) -> Vec<ParamInfo> {
         match self {
             Self::HGroup { items, .. }
@@ -74,34 +520,128 @@ impl GetParmInfo for LayoutItem {
                 items.iter().flat_map(GetParmInfo::get_param_info).collect()
             }
             Self::VSlider {
-                shortname, varname, ..
+                label,
+                shortname,
+                address,
+                varname,
+                init,
+                min,
+                max,
+                step,
+                meta,
+                ..
             }
             | Self::HSlider {
-                shortname, varname, ..
-            }
-            | Self::NEntry {
-                shortname, varname, ..
-            }
-            | Self::Button {
-                shortname, varname, ..
+                label,
+                shortname,
+                address,
+                varname,
+                init,
+                min,
+                max,
+                step,
+                meta,
+                ..
+            } => ParamInfo::new(
+                if matches!(self, Self::VSlider { .. }) {
+                    Kind::VSlider
+                } else {
+                    Kind::HSlider
+                },
+                label,
+                shortname,
+                address,
+                varname,
+                [*init, *min, *max, *step],
+                meta,
+            ),
+            Self::NEntry {
+                label,
+                shortname,
+                address,
+                varname,
+                init,
+                min,
+                max,
+                step,
+                meta,
+                ..
+            } => ParamInfo::new(
+                Kind::NEntry,
+                label,
+                shortname,
+                address,
+                varname,
+                [init.unwrap_or_default(), *min, *max, *step],
+                meta,
+            ),
+            Self::Button {
+                label,
+                shortname,
+                address,
+                varname,
+                meta,
+                ..
             }
             | Self::CheckBox {
-                shortname, varname, ..
-            } => ParamInfo::active(&shortname.to_camel_case(), varname),
+                label,
+                shortname,
+                address,
+                varname,
+                meta,
+                ..
+            } => ParamInfo::new(
+                if matches!(self, Self::Button { .. }) {
+                    Kind::Button
+                } else {
+                    Kind::CheckBox
+                },
+                label,
+                shortname,
+                address,
+                varname,
+                [0.0, 0.0, 1.0, 1.0],
+                meta,
+            ),
             Self::VBarGraph {
-                shortname, varname, ..
+                label,
+                shortname,
+                address,
+                varname,
+                min,
+                max,
+                meta,
+                ..
             }
             | Self::HBarGraph {
-                shortname, varname, ..
-            } => ParamInfo::passive(&shortname.to_camel_case(), varname),
-            Self::Soundfile {
-                address, varname, ..
-            } => ParamInfo::active(address, varname),
+                label,
+                shortname,
+                address,
+                varname,
+                min,
+                max,
+                meta,
+                ..
+            } => ParamInfo::new(
+                if matches!(self, Self::VBarGraph { .. }) {
+                    Kind::VBarGraph
+                } else {
+                    Kind::HBarGraph
+                },
+                label,
+                shortname,
+                address,
+                varname,
+                [*min, *min, *max, 0.0],
+                meta,
+            ),
+            // Soundfiles have no value, see `widget_indices` for their `ParamIndex`.
+            Self::Soundfile { .. } => Vec::new(),
         }
     }
 }
 
-fn create_qualified_enum(infos: &[&ParamInfo], is_active: bool) -> TokenStream {
+fn create_qualified_enum(infos: &[&ParamInfo], is_active: bool, options: UiOptions) -> TokenStream {
     let enum_name = if is_active {
         enum_active_value_ident()
     } else {
@@ -116,24 +656,26 @@ fn create_qualified_enum(infos: &[&ParamInfo], is_active: bool) -> TokenStream {
         .iter()
         .map(|param_info| format_ident!("{}", param_info.shortname).to_token_stream())
         .collect();
+    let docs = infos.iter().map(|param_info| param_info.doc());
+    let serde = serde_derives(options);
     quote! {
-        #[derive(Debug, Clone, Copy, PartialEq, Display, EnumIter, EnumCount,EnumDiscriminants,VariantNames)]
+        #[derive(Debug, Clone, Copy, PartialEq, Display, EnumIter, EnumCount,EnumDiscriminants,VariantNames #serde)]
         #[strum_discriminants(derive(Display,EnumIter, EnumCount,IntoStaticStr,VariantArray,VariantNames,Hash))]
         #[strum_discriminants(name(#discriminants_name))]
         pub enum #enum_name {
-            #(#i(FaustFloat)),*
+            #(#docs #i(FaustFloat)),*
         }
     }
 }
 
-fn create_active_impl(infos: &[&ParamInfo], dsp_name: &Ident) -> TokenStream {
+fn create_active_impl(infos: &[&ParamInfo], dsp_name: &Ident, access: ParamAccess) -> TokenStream {
     let enum_name = enum_active_value_ident();
     let matches_set: Vec<TokenStream> = infos
         .iter()
         .map(|param_info| {
             let shortname = format_ident!("{}", param_info.shortname);
-            let varname = format_ident!("{}", param_info.varname);
-            quote! { #enum_name::#shortname(value) => dsp.#varname = *value}
+            let set = param_info.set(access, &quote! { *value });
+            quote! { #enum_name::#shortname(value) => #set}
         })
         .collect();
     let matches_get: Vec<TokenStream> = infos
@@ -148,8 +690,8 @@ fn create_active_impl(infos: &[&ParamInfo], dsp_name: &Ident) -> TokenStream {
         .iter()
         .map(|param_info| {
             let shortname = format_ident!("{}", param_info.shortname);
-            let varname = format_ident!("{}", param_info.varname);
-            quote! { #enum_name_discriminant::#shortname => dsp.#varname = value}
+            let set = param_info.set(access, &quote! { value });
+            quote! { #enum_name_discriminant::#shortname => #set}
         })
         .collect();
     let matches_value: Vec<TokenStream> = infos
@@ -159,6 +701,9 @@ fn create_active_impl(infos: &[&ParamInfo], dsp_name: &Ident) -> TokenStream {
             quote! { #enum_name_discriminant::#shortname => #enum_name::#shortname(value)}
         })
         .collect();
+    let matches_param_index = param_index_matches(infos, &enum_name_discriminant);
+    let declared = declared_methods(infos, &enum_name_discriminant);
+    let normalized = normalized_methods(infos, &enum_name_discriminant, dsp_name, access);
     quote! {
         impl UISelfSet<#dsp_name,FaustFloat> for #enum_name {
             fn set(&self, dsp: &mut #dsp_name) {
@@ -185,11 +730,24 @@ fn create_active_impl(infos: &[&ParamInfo], dsp_name: &Ident) -> TokenStream {
                     #(#matches_value ),*
                 }
             }
+            pub const fn param_index(&self) -> TypedParamIndex<#dsp_name> {
+                // SAFETY: the indices are taken from the json description of the dsp.
+                unsafe {
+                    match self {
+                        #(#matches_param_index ),*
+                    }
+                }
+            }
+            pub fn set_normalized(&self, dsp: &mut #dsp_name, normalized: FaustFloat) {
+                UISet::set(self, dsp, self.from_normalized(normalized));
+            }
+            #declared
+            #normalized
         }
     }
 }
 
-fn create_passive_impl(infos: &[&ParamInfo], dsp_name: &Ident) -> TokenStream {
+fn create_passive_impl(infos: &[&ParamInfo], dsp_name: &Ident, access: ParamAccess) -> TokenStream {
     let enum_name: Ident = enum_passive_value_ident();
 
     let enum_name_discriminant = enum_passive_discriminants_ident();
@@ -198,8 +756,8 @@ fn create_passive_impl(infos: &[&ParamInfo], dsp_name: &Ident) -> TokenStream {
         .iter()
         .map(|param_info| {
             let shortname = format_ident!("{}", param_info.shortname);
-            let varname = format_ident!("{}", param_info.varname);
-            quote! { #enum_name_discriminant::#shortname => dsp.#varname}
+            let get = param_info.get(access);
+            quote! { #enum_name_discriminant::#shortname => #get}
         })
         .collect();
 
@@ -207,8 +765,8 @@ fn create_passive_impl(infos: &[&ParamInfo], dsp_name: &Ident) -> TokenStream {
         .iter()
         .map(|param_info| {
             let shortname = format_ident!("{}", param_info.shortname);
-            let varname = format_ident!("{}", param_info.varname);
-            quote! { #enum_name_discriminant::#shortname => #enum_name::#shortname(dsp.#varname)}
+            let get = param_info.get(access);
+            quote! { #enum_name_discriminant::#shortname => #enum_name::#shortname(#get)}
         })
         .collect();
     let matches_value: Vec<TokenStream> = infos
@@ -218,6 +776,9 @@ fn create_passive_impl(infos: &[&ParamInfo], dsp_name: &Ident) -> TokenStream {
             quote! { #enum_name_discriminant::#shortname => #enum_name::#shortname(value)}
         })
         .collect();
+    let matches_param_index = param_index_matches(infos, &enum_name_discriminant);
+    let declared = declared_methods(infos, &enum_name_discriminant);
+    let normalized = normalized_methods(infos, &enum_name_discriminant, &dsp_name, access);
 
     quote! {
         impl UIGet<#dsp_name> for #enum_name_discriminant {
@@ -240,29 +801,279 @@ fn create_passive_impl(infos: &[&ParamInfo], dsp_name: &Ident) -> TokenStream {
                     #(#matches_value ),*
                 }
             }
+            pub const fn param_index(&self) -> TypedParamIndex<#dsp_name> {
+                // SAFETY: the indices are taken from the json description of the dsp.
+                unsafe {
+                    match self {
+                        #(#matches_param_index ),*
+                    }
+                }
+            }
+            #declared
+            #normalized
+        }
+    }
+}
+
+/// The names of the choice enums, `None` for widgets without choices.
+fn choice_names(infos: &[&ParamInfo], dsp_name: &Ident) -> Vec<Option<Ident>> {
+    let mut names = Names::reserved(
+        [
+            dsp_name.clone(),
+            enum_active_value_ident(),
+            enum_active_discriminants_ident(),
+            enum_passive_value_ident(),
+            enum_passive_discriminants_ident(),
+        ]
+        .iter()
+        .map(ToString::to_string)
+        .chain(RESERVED.iter().map(ToString::to_string)),
+    );
+    infos
+        .iter()
+        .map(|param_info| {
+            let shortname = &param_info.shortname;
+            if param_info.choices.is_empty() {
+                None
+            } else if names.contains(shortname) {
+                Some(names.take(&format_ident!("{shortname}Choice")))
+            } else {
+                Some(names.take(shortname))
+            }
+        })
+        .collect()
+}
+
+/// An enum per widget with `style:menu` or `style:radio` metadata,
+/// named after the widget and with a variant per choice.
+fn create_choice_enums(
+    infos: &[&ParamInfo],
+    choice_names: &[Option<Ident>],
+    dsp_name: &Ident,
+    options: UiOptions,
+) -> TokenStream {
+    let active_value = enum_active_value_ident();
+    let serde = serde_derives(options);
+    infos
+        .iter()
+        .zip(choice_names)
+        .filter_map(|(param_info, choice_name)| Some((param_info, choice_name.as_ref()?)))
+        .map(|(param_info, choice_name)| {
+            let shortname = &param_info.shortname;
+            let variants = param_info.choice_variants();
+            let labels = param_info.choices.iter().map(|(label, _)| label);
+            let values = param_info.choices.iter().map(|(_, value)| float(*value));
+            let first = &variants[0];
+            let set = param_info.set(options.access, &quote! { self.value() });
+            let get = param_info.get(options.access);
+            quote! {
+                #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Display, EnumIter, EnumCount, IntoStaticStr, VariantArray, VariantNames #serde)]
+                pub enum #choice_name {
+                    #(#[strum(serialize = #labels)] #variants),*
+                }
+                impl #choice_name {
+                    pub const fn value(&self) -> FaustFloat {
+                        match self {
+                            #(#choice_name::#variants => #values),*
+                        }
+                    }
+                    pub fn from_value(value: FaustFloat) -> Self {
+                        <Self as VariantArray>::VARIANTS.iter().copied().fold(#choice_name::#first, |nearest, choice| {
+                            if (choice.value() - value).abs() < (nearest.value() - value).abs() {
+                                choice
+                            } else {
+                                nearest
+                            }
+                        })
+                    }
+                    pub fn read(dsp: &#dsp_name) -> Self {
+                        Self::from_value(#get)
+                    }
+                }
+                impl UISelfSet<#dsp_name, FaustFloat> for #choice_name {
+                    fn set(&self, dsp: &mut #dsp_name) {
+                        #set;
+                    }
+                    fn get(&self) -> FaustFloat {
+                        self.value()
+                    }
+                }
+                impl From<#choice_name> for #active_value {
+                    fn from(choice: #choice_name) -> Self {
+                        #active_value::#shortname(choice.value())
+                    }
+                }
+            }
+        })
+        .collect()
+}
+
+/// A getter named after the shortname for every widget and a setter for the active ones.
+fn create_accessors(infos: &[ParamInfo], dsp_name: &Ident, access: ParamAccess) -> TokenStream {
+    let mut names = Names::reserved(DSP_METHODS.iter().copied());
+    let methods = infos.iter().map(|param_info| {
+        let (getter, setter) = names.accessors(&param_info.name);
+        let enum_name = if param_info.kind.is_active() {
+            enum_active_value_ident()
+        } else {
+            enum_passive_value_ident()
+        };
+        let shortname = &param_info.shortname;
+        let range = param_info.range_doc();
+        let get_doc = doc::attributes(&[
+            &format!("The value of [`{enum_name}::{shortname}`]."),
+            &range,
+        ]);
+        let get = param_info.get(access);
+        let getter = quote! {
+            #get_doc
+            pub fn #getter(&self) -> FaustFloat {
+                let dsp = self;
+                #get
+            }
+        };
+        if !param_info.kind.is_active() {
+            return getter;
+        }
+        let set_doc = doc::attributes(&[&format!("Sets [`{enum_name}::{shortname}`]."), &range]);
+        let set = param_info.set(access, &quote! { value });
+        quote! {
+            #getter
+            #set_doc
+            pub fn #setter(&mut self, value: FaustFloat) {
+                let dsp = self;
+                #set;
+            }
+        }
+    });
+    quote! {
+        impl #dsp_name {
+            #(#methods)*
         }
     }
 }
 
-fn create_from_paraminfo(v: &[ParamInfo], dsp_name: &Ident) -> TokenStream {
-    let active: Vec<&ParamInfo> = v.iter().filter(|i| i.is_active).collect();
-    let passive: Vec<&ParamInfo> = v.iter().filter(|i| !i.is_active).collect();
+/// `dsp.set(value)` for the values of `UIActiveValue` and the choice enums.
+fn create_dsp_set(dsp_name: &Ident) -> TokenStream {
+    quote! {
+        impl #dsp_name {
+            pub fn set(&mut self, value: impl UISelfSet<Self, FaustFloat>) {
+                value.set(self);
+            }
+        }
+    }
+}
+
+/// `, serde::Serialize, serde::Deserialize` if selected, to be appended to a derive list.
+fn serde_derives(options: UiOptions) -> TokenStream {
+    if options.serde {
+        quote! { , serde::Serialize, serde::Deserialize }
+    } else {
+        TokenStream::new()
+    }
+}
+
+/// `Params` with a field per active widget, typed with the choice enum for menus.
+fn create_params(
+    infos: &[&ParamInfo],
+    choice_names: &[Option<Ident>],
+    dsp_name: &Ident,
+    options: UiOptions,
+) -> TokenStream {
+    let mut names = Names::default();
+    let fields: Vec<Ident> = infos
+        .iter()
+        .map(|param_info| names.snake(&param_info.name))
+        .collect();
+    let mut types = Vec::new();
+    let mut defaults = Vec::new();
+    let mut applies = Vec::new();
+    let mut reads = Vec::new();
+    for ((param_info, choice_name), field) in infos.iter().zip(choice_names).zip(&fields) {
+        if let Some(choice_name) = choice_name {
+            let (_, nearest) = param_info
+                .choices
+                .iter()
+                .zip(param_info.choice_variants())
+                .min_by(|((_, a), _), ((_, b), _)| {
+                    (a - param_info.init)
+                        .abs()
+                        .total_cmp(&(b - param_info.init).abs())
+                })
+                .expect("a menu has choices");
+            types.push(quote! { #choice_name });
+            defaults.push(quote! { #choice_name::#nearest });
+            applies.push(quote! { UISelfSet::set(&self.#field, dsp) });
+            reads.push(quote! { #choice_name::read(dsp) });
+        } else {
+            let init = float(param_info.init);
+            types.push(quote! { FaustFloat });
+            defaults.push(quote! { #init });
+            applies.push(param_info.set(options.access, &quote! { self.#field }));
+            reads.push(param_info.get(options.access));
+        }
+    }
+    let serde = serde_derives(options);
+    quote! {
+        #[derive(Debug, Clone, Copy, PartialEq #serde)]
+        pub struct Params {
+            #(pub #fields: #types),*
+        }
+        impl Default for Params {
+            fn default() -> Self {
+                Self {
+                    #(#fields: #defaults),*
+                }
+            }
+        }
+        impl Params {
+            pub fn apply(&self, dsp: &mut #dsp_name) {
+                #(#applies;)*
+            }
+            pub fn read(dsp: &#dsp_name) -> Self {
+                Self {
+                    #(#fields: #reads),*
+                }
+            }
+        }
+    }
+}
+
+fn create_from_paraminfo(v: &[ParamInfo], dsp_name: &Ident, options: UiOptions) -> TokenStream {
+    let access = options.access;
+    let active: Vec<&ParamInfo> = v.iter().filter(|i| i.kind.is_active()).collect();
+    let passive: Vec<&ParamInfo> = v.iter().filter(|i| !i.kind.is_active()).collect();
     let (active_enum, active_impl) = if active.is_empty() {
         (TokenStream::new(), TokenStream::new())
     } else {
+        let active_impl = create_active_impl(&active, dsp_name, access);
+        let choice_names = choice_names(&active, dsp_name);
+        let choice_enums = create_choice_enums(&active, &choice_names, dsp_name, options);
+        let params = create_params(&active, &choice_names, dsp_name, options);
+        let dsp_set = create_dsp_set(dsp_name);
         (
-            create_qualified_enum(&active, true),
-            create_active_impl(&active, dsp_name),
+            create_qualified_enum(&active, true, options),
+            quote! {
+                #active_impl
+                #choice_enums
+                #params
+                #dsp_set
+            },
         )
     };
     let (passive_enum, passive_impl) = if passive.is_empty() {
         (TokenStream::new(), TokenStream::new())
     } else {
         (
-            create_qualified_enum(&passive, false),
-            create_passive_impl(&passive, dsp_name),
+            create_qualified_enum(&passive, false, options),
+            create_passive_impl(&passive, dsp_name, access),
         )
     };
+    let accessors = if options.accessors && !v.is_empty() {
+        create_accessors(v, dsp_name, access)
+    } else {
+        TokenStream::new()
+    };
     quote::quote! {
         use strum::{Display,EnumIter,EnumCount,EnumDiscriminants,IntoStaticStr,VariantArray,VariantNames};
 
@@ -270,11 +1081,46 @@ fn create_from_paraminfo(v: &[ParamInfo], dsp_name: &Ident) -> TokenStream {
         #active_impl
         #passive_enum
         #passive_impl
+        #accessors
     }
 }
 
+/// The variants of the widgets in the enums, in the order of the layout.
+pub(crate) fn variant_names(dsp_json: &FaustJson) -> Vec<Ident> {
+    dsp_json
+        .get_param_info()
+        .into_iter()
+        .map(|param_info| param_info.shortname)
+        .collect()
+}
+
 #[must_use]
 pub fn create(dsp_json: &FaustJson, dsp_name: &Ident) -> TokenStream {
+    create_with_access(dsp_json, dsp_name, ParamAccess::Field)
+}
+
+#[must_use]
+pub fn create_with_access(
+    dsp_json: &FaustJson,
+    dsp_name: &Ident,
+    access: ParamAccess,
+) -> TokenStream {
+    create_with_options(
+        dsp_json,
+        dsp_name,
+        UiOptions {
+            access,
+            ..UiOptions::default()
+        },
+    )
+}
+
+#[must_use]
+pub fn create_with_options(
+    dsp_json: &FaustJson,
+    dsp_name: &Ident,
+    options: UiOptions,
+) -> TokenStream {
     let param_info = dsp_json.get_param_info();
-    create_from_paraminfo(&param_info, dsp_name)
+    create_from_paraminfo(&param_info, dsp_name, options)
 }
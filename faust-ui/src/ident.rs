@@ -0,0 +1,129 @@
+//! Rust identifiers for labels, which may contain any character.
+
+use heck::{CamelCase, SnakeCase};
+use quote::format_ident;
+use std::collections::HashSet;
+use syn::Ident;
+
+/// Strict and reserved keywords, none of them can be a field name.
+const KEYWORDS: &[&str] = &[
+    "abstract", "as", "async", "await", "become", "box", "break", "const", "continue", "crate",
+    "do", "dyn", "else", "enum", "extern", "false", "final", "fn", "for", "gen", "if", "impl",
+    "in", "let", "loop", "macro", "match", "mod", "move", "mut", "override", "priv", "pub", "ref",
+    "return", "self", "Self", "static", "struct", "super", "trait", "true", "try", "type",
+    "typeof", "unsafe", "unsized", "use", "virtual", "where", "while", "yield",
+];
+
+/// `label` with every character but ASCII letters and digits replaced by a space,
+/// which separates words.
+fn words(label: &str) -> String {
+    label
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { ' ' })
+        .collect()
+}
+
+/// The words of `label` in CamelCase, to be appended to another name.
+#[must_use]
+pub fn camel_words(label: &str) -> String {
+    words(label).to_camel_case()
+}
+
+/// A CamelCase type or variant name for `label`.
+///
+/// `V` is prepended to names starting with a digit and to `Self`.
+#[must_use]
+pub fn camel(label: &str) -> String {
+    let mut name = camel_words(label);
+    if name.is_empty() {
+        name.push_str("Unnamed");
+    }
+    if name.starts_with(|c: char| c.is_ascii_digit()) || name == "Self" {
+        name.insert(0, 'V');
+    }
+    name
+}
+
+/// A `snake_case` field name for `label`.
+///
+/// `_` is prepended to names starting with a digit and appended to keywords.
+#[must_use]
+pub fn snake(label: &str) -> String {
+    let mut name = words(label).to_snake_case();
+    if name.is_empty() {
+        name.push_str("unnamed");
+    }
+    if name.starts_with(|c: char| c.is_ascii_digit()) {
+        name.insert(0, '_');
+    }
+    if KEYWORDS.contains(&name.as_str()) {
+        name.push('_');
+    }
+    name
+}
+
+/// The names taken in a scope, like the variants of an enum or the fields of a struct.
+#[derive(Debug, Default)]
+pub struct Names {
+    used: HashSet<String>,
+}
+
+impl Names {
+    /// A scope in which `reserved` are taken already.
+    pub fn reserved<I, S>(reserved: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        Self {
+            used: reserved.into_iter().map(Into::into).collect(),
+        }
+    }
+
+    pub fn contains(&self, name: &Ident) -> bool {
+        self.used.contains(&name.to_string())
+    }
+
+    /// `name`, numbered from 2 if it is taken.
+    pub fn take(&mut self, name: &Ident) -> Ident {
+        self.unique(&name.to_string(), "")
+    }
+
+    /// The CamelCase name of `label`, numbered from 2 if it is taken.
+    pub fn camel(&mut self, label: &str) -> Ident {
+        self.unique(&camel(label), "")
+    }
+
+    /// The `snake_case` name of `label`, numbered from `_2` if it is taken.
+    pub fn snake(&mut self, label: &str) -> Ident {
+        self.unique(&snake(label), "_")
+    }
+
+    /// The `snake_case` getter name of `label` and its setter `set_{name}`,
+    /// numbered from `_2` if either of them is taken.
+    pub fn accessors(&mut self, label: &str) -> (Ident, Ident) {
+        let name = snake(label);
+        let setter = |getter: &str| format!("set_{}", getter.trim_matches('_'));
+        let mut unique = name.clone();
+        let mut n = 1;
+        while self.used.contains(&unique) || self.used.contains(&setter(&unique)) {
+            n += 1;
+            unique = format!("{name}_{n}");
+        }
+        let set = setter(&unique);
+        self.used.insert(set.clone());
+        self.used.insert(unique.clone());
+        (format_ident!("{unique}"), format_ident!("{set}"))
+    }
+
+    fn unique(&mut self, name: &str, separator: &str) -> Ident {
+        let mut unique = name.to_owned();
+        let mut n = 1;
+        while self.used.contains(&unique) {
+            n += 1;
+            unique = format!("{name}{separator}{n}");
+        }
+        self.used.insert(unique.clone());
+        format_ident!("{unique}")
+    }
+}
@@ -9,21 +9,74 @@
 )]
 #![allow(clippy::missing_panics_doc)]
 
+#[cfg(test)]
+use serde_json as _;
+
 use faust_json::FaustJson;
 use proc_macro2::TokenStream;
 use quote::{format_ident, quote};
 
+mod doc;
 pub mod enum_interface;
+mod ident;
 pub mod struct_interface;
 
+/// How the generated code reads and writes the parameters of the dsp.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ParamAccess {
+    /// Direct access to the fields of the struct generated by the Rust backend.
+    #[default]
+    Field,
+    /// Access through `FaustDsp::set_param` and `FaustDsp::get_param`,
+    /// for dsps without public fields like the wrappers of the C backend.
+    Index,
+}
+
+/// Selects what the generated code contains besides the enums.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct UiOptions {
+    pub access: ParamAccess,
+    /// Derives `serde::Serialize` and `serde::Deserialize` for the value enums, the choice enums
+    /// and `Params`. The crate of the dsp needs `serde` with the `derive` feature.
+    pub serde: bool,
+    /// Generates inherent methods on the dsp struct named after the shortnames,
+    /// `volume()` to read every widget and `set_volume()` to set the active ones.
+    pub accessors: bool,
+}
+
 #[must_use]
 pub fn generate_ui_code(faust_json: &FaustJson, struct_name: impl AsRef<str>) -> TokenStream {
+    generate_ui_code_with_access(faust_json, struct_name, ParamAccess::Field)
+}
+
+#[must_use]
+pub fn generate_ui_code_with_access(
+    faust_json: &FaustJson,
+    struct_name: impl AsRef<str>,
+    access: ParamAccess,
+) -> TokenStream {
+    generate_ui_code_with_options(
+        faust_json,
+        struct_name,
+        UiOptions {
+            access,
+            ..UiOptions::default()
+        },
+    )
+}
+
+#[must_use]
+pub fn generate_ui_code_with_options(
+    faust_json: &FaustJson,
+    struct_name: impl AsRef<str>,
+    options: UiOptions,
+) -> TokenStream {
     let struct_name = format_ident!("{}", struct_name.as_ref());
 
     let ui_static_name = format_ident!("DSP_UI");
     let ui_type = format_ident!("DspUi");
 
-    let ui_enum = enum_interface::create(faust_json, &struct_name);
+    let ui_enum = enum_interface::create_with_options(faust_json, &struct_name, options);
     let struct_interface = struct_interface::create(faust_json, &ui_static_name, &ui_type);
 
     let ui_code = quote! {
@@ -32,3 +85,10 @@ pub fn generate_ui_code(faust_json: &FaustJson, struct_name: impl AsRef<str>) ->
     };
     ui_code
 }
+
+/// `#[doc]` attributes for the dsp struct with the name, description, author, license and
+/// version declared in the dsp.
+#[must_use]
+pub fn generate_dsp_doc(faust_json: &FaustJson) -> TokenStream {
+    doc::dsp(faust_json)
+}
@@ -1,122 +1,93 @@
 use faust_json::{FaustJson, LayoutItem};
-use heck::CamelCase;
 use proc_macro2::TokenStream;
 use quote::{format_ident, quote};
+use std::vec;
 use syn::Ident;
 
-use crate::enum_interface;
+use crate::{doc, enum_interface, ident};
 
 #[derive(Clone)]
 enum StructInfo {
     GroupInfo {
-        label: Ident,
+        /// Field of the group in the struct of the enclosing group.
+        field: Ident,
+        label: String,
         type_name: Ident,
         items: Vec<StructInfo>,
     },
     UIInfo {
-        label: Ident,
+        field: Ident,
         type_name: Ident,
         shortname: Ident,
     },
 }
 
-impl StructInfo {
-    #[allow(unused_variables)]
-    pub fn get_label(&self) -> String {
-        match self {
-            Self::GroupInfo {
-                label,
-                type_name,
-                items,
-            } => label.to_string(),
-            Self::UIInfo {
-                label,
-                type_name,
-                shortname,
-            } => label.to_string(),
-        }
-    }
-    pub fn active(label: &str, shortname: &str) -> Self {
-        Self::UIInfo {
-            type_name: enum_interface::enum_active_discriminants_ident(),
-            label: format_ident!("{label}"),
-            shortname: format_ident!("{shortname}"),
-        }
-    }
-    pub fn passive(label: &str, shortname: &str) -> Self {
-        Self::UIInfo {
-            type_name: enum_interface::enum_passive_discriminants_ident(),
-            label: format_ident!("{label}"),
-            shortname: format_ident!("{shortname}"),
-        }
+/// The names of the structs and the variants of the widgets, in the order of the layout.
+struct Names {
+    types: ident::Names,
+    variants: vec::IntoIter<Ident>,
+}
+
+impl Names {
+    fn variant(&mut self) -> Ident {
+        self.variants.next().expect("a variant per widget")
     }
 }
 
 trait GetGroupInfo {
-    fn get_ui_structure(&self, parent_type: &Ident) -> StructInfo;
+    /// `fields` are the names taken in the struct of the enclosing group.
+    fn get_ui_structure(
+        &self,
+        parent_type: &Ident,
+        fields: &mut ident::Names,
+        names: &mut Names,
+    ) -> Option<StructInfo>;
 }
 
 impl GetGroupInfo for LayoutItem {
-    fn get_ui_structure(&self, parent_type: &Ident) -> StructInfo {
+    fn get_ui_structure(
+        &self,
+        parent_type: &Ident,
+        fields: &mut ident::Names,
+        names: &mut Names,
+    ) -> Option<StructInfo> {
         match self {
-            Self::TGroup { label, items, .. } => {
-                let type_name = format_ident!("{parent_type}{}", label.to_camel_case());
-                let label = format_ident!("{label}");
-                StructInfo::GroupInfo {
-                    type_name: type_name.clone(),
-                    label,
-                    items: items
-                        .iter()
-                        .map(|items| items.get_ui_structure(&type_name))
-                        .collect(),
-                }
+            Self::TGroup { label, items, .. }
+            | Self::VGroup { label, items, .. }
+            | Self::HGroup { label, items, .. } => {
+                let type_name = names
+                    .types
+                    .take(&format_ident!("{parent_type}{}", ident::camel_words(label)));
+                let mut item_fields = ident::Names::default();
+                let items = items
+                    .iter()
+                    .filter_map(|item| item.get_ui_structure(&type_name, &mut item_fields, names))
+                    .collect();
+                Some(StructInfo::GroupInfo {
+                    field: fields.snake(label),
+                    label: label.clone(),
+                    type_name,
+                    items,
+                })
             }
-            Self::VGroup { label, items, .. } => {
-                let type_name = format_ident!("{parent_type}{}", label.to_camel_case());
-                let label = format_ident!("{label}");
-                StructInfo::GroupInfo {
-                    type_name: type_name.clone(),
-                    label,
-                    items: items
-                        .iter()
-                        .map(|items| items.get_ui_structure(&type_name))
-                        .collect(),
-                }
-            }
-            Self::HGroup { label, items, .. } => {
-                let type_name = format_ident!("{parent_type}{}", label.to_camel_case());
-                let label = format_ident!("{label}");
-                StructInfo::GroupInfo {
-                    type_name: type_name.clone(),
-                    label,
-                    items: items
-                        .iter()
-                        .map(|items| items.get_ui_structure(&type_name))
-                        .collect(),
-                }
-            }
-            Self::VSlider {
-                label, shortname, ..
-            }
-            | Self::HSlider {
-                label, shortname, ..
-            }
-            | Self::NEntry {
-                label, shortname, ..
+            Self::VSlider { label, .. }
+            | Self::HSlider { label, .. }
+            | Self::NEntry { label, .. }
+            | Self::Button { label, .. }
+            | Self::CheckBox { label, .. } => Some(StructInfo::UIInfo {
+                field: fields.snake(label),
+                type_name: enum_interface::enum_active_discriminants_ident(),
+                shortname: names.variant(),
+            }),
+            Self::VBarGraph { label, .. } | Self::HBarGraph { label, .. } => {
+                Some(StructInfo::UIInfo {
+                    field: fields.snake(label),
+                    type_name: enum_interface::enum_passive_discriminants_ident(),
+                    shortname: names.variant(),
+                })
             }
-            | Self::Button {
-                label, shortname, ..
-            }
-            | Self::CheckBox {
-                label, shortname, ..
-            } => StructInfo::active(label, &shortname.to_camel_case()),
-            Self::VBarGraph {
-                label, shortname, ..
-            }
-            | Self::HBarGraph {
-                label, shortname, ..
-            } => StructInfo::passive(label, &shortname.to_camel_case()),
-            Self::Soundfile { address, .. } => StructInfo::active(address, address),
+            // Soundfiles are not part of the enums.
+            Self::Soundfile { .. } => None,
         }
     }
 }
@@ -140,13 +111,13 @@ fn create_struct_fields(items: &[StructInfo]) -> Vec<TokenStream> {
         .iter()
         .map(|i: &StructInfo| match i {
             StructInfo::UIInfo {
-                label, type_name, ..
+                field, type_name, ..
             }
             | StructInfo::GroupInfo {
-                label, type_name, ..
+                field, type_name, ..
             } => {
                 quote! {
-                    pub #label : #type_name
+                    pub #field : #type_name
                 }
             }
         })
@@ -158,19 +129,19 @@ fn create_struct_defaults(items: &[StructInfo]) -> Vec<TokenStream> {
         .iter()
         .map(|i: &StructInfo| match i {
             StructInfo::GroupInfo {
-                label, type_name, ..
+                field, type_name, ..
             } => {
                 quote! {
-                    #label : #type_name::static_ui()
+                    #field : #type_name::static_ui()
                 }
             }
             StructInfo::UIInfo {
-                label,
+                field,
                 type_name,
                 shortname,
             } => {
                 quote! {
-                    #label: #type_name::#shortname
+                    #field: #type_name::#shortname
                 }
             }
         })
@@ -180,11 +151,16 @@ fn create_struct_defaults(items: &[StructInfo]) -> Vec<TokenStream> {
 fn create_structs(si: &StructInfo) -> TokenStream {
     match si {
         StructInfo::GroupInfo {
-            items, type_name, ..
+            label,
+            items,
+            type_name,
+            ..
         } => {
             let fields: Vec<TokenStream> = create_struct_fields(items);
             let defaults: Vec<TokenStream> = create_struct_defaults(items);
+            let doc = doc::attributes(&[format!("The `{label}` group.")]);
             quote::quote! {
+                #doc
                 #[derive(Debug)]
                 pub struct #type_name { #(#fields),* }
 
@@ -194,6 +170,9 @@ fn create_structs(si: &StructInfo) -> TokenStream {
                             #(#defaults),*
                         }
                     }
+                    pub const fn label(&self) -> &'static str {
+                        #label
+                    }
                 }
 
             }
@@ -205,18 +184,25 @@ fn create_structs(si: &StructInfo) -> TokenStream {
 }
 
 pub fn create(dsp_json: &FaustJson, ui_static_name: &Ident, ui_type: &Ident) -> TokenStream {
+    let mut names = Names {
+        types: ident::Names::default(),
+        variants: enum_interface::variant_names(dsp_json).into_iter(),
+    };
     let ui_info_tree = dsp_json
         .ui
         .iter()
-        .map(|items| items.get_ui_structure(ui_type))
+        .filter_map(|items| {
+            items.get_ui_structure(ui_type, &mut ident::Names::default(), &mut names)
+        })
         .collect::<Vec<_>>();
     assert_eq!(ui_info_tree.len(), 1);
-    let head_label = ui_info_tree
-        .first()
-        .expect("cannot fail")
-        .get_label()
-        .to_camel_case();
-    let head_type = format_ident!("{ui_type}{}", head_label);
+    let StructInfo::GroupInfo {
+        type_name: head_type,
+        ..
+    } = ui_info_tree.first().expect("cannot fail")
+    else {
+        panic!("the ui has no top level group")
+    };
 
     // i need to unroll the tree into a list of structs
     let ui_info_list = flat_ui_infos(&ui_info_tree);
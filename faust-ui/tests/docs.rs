@@ -0,0 +1,88 @@
+use faust_json::FaustJson;
+use faust_ui::generate_ui_code;
+use syn::{Attribute, Expr, File, Item, Lit, Meta};
+
+fn generate() -> File {
+    let json = r#"{"name":"volume","filename":"volume.dsp","version":"2.0","compile_options":"",
+        "size":0,"inputs":2,"outputs":2,"ui":[{"type":"vgroup","label":"volume","items":[
+            {"type":"vbargraph","label":"level","shortname":"level","address":"/volume/level",
+              "varname":"fVbargraph0","meta":[{"unit":"dB"}],"min":-60,"max":5},
+            {"type":"vslider","label":"volume","shortname":"volume","address":"/volume/volume",
+              "varname":"fVslider0","meta":[{"tooltip":"Output gain"},{"unit":"dB"}],
+              "init":0,"min":-70,"max":4,"step":0.1},
+            {"type":"button","label":"mute","shortname":"mute","address":"/volume/mute",
+              "varname":"fButton0"}]}]}"#;
+    let faust_json: FaustJson = serde_json::from_str(json).expect("valid json");
+    syn::parse2(generate_ui_code(&faust_json, "Volume")).expect("valid rust")
+}
+
+fn doc(attrs: &[Attribute]) -> Vec<String> {
+    attrs
+        .iter()
+        .filter_map(|attr| match &attr.meta {
+            Meta::NameValue(meta) if meta.path.is_ident("doc") => match &meta.value {
+                Expr::Lit(expr) => match &expr.lit {
+                    Lit::Str(doc) => Some(doc.value()),
+                    _ => None,
+                },
+                _ => None,
+            },
+            _ => None,
+        })
+        .collect()
+}
+
+fn variant_docs(file: &File, name: &str) -> Vec<Vec<String>> {
+    file.items
+        .iter()
+        .find_map(|item| match item {
+            Item::Enum(item) if item.ident == name => Some(
+                item.variants
+                    .iter()
+                    .map(|variant| doc(&variant.attrs))
+                    .collect(),
+            ),
+            _ => None,
+        })
+        .unwrap_or_else(|| panic!("no enum {name}"))
+}
+
+#[test]
+fn documents_widgets() {
+    let file = generate();
+    assert_eq!(
+        variant_docs(&file, "UIActiveValue"),
+        [
+            vec![
+                " Vertical slider `/volume/volume`.",
+                "",
+                " Output gain",
+                "",
+                " `-70` to `4` dB, step `0.1`, initially `0`.",
+            ],
+            vec![
+                " Button `/volume/mute`.",
+                "",
+                " `1` while pressed, `0` otherwise.",
+            ],
+        ]
+    );
+    assert_eq!(
+        variant_docs(&file, "UIPassiveValue"),
+        [vec![
+            " Vertical bargraph `/volume/level`.",
+            "",
+            " `-60` to `5` dB.",
+        ]]
+    );
+}
+
+#[test]
+fn documents_groups() {
+    let file = generate();
+    let group_doc = file.items.iter().find_map(|item| match item {
+        Item::Struct(item) if item.ident == "DspUiVolume" => Some(doc(&item.attrs)),
+        _ => None,
+    });
+    assert_eq!(group_doc, Some(vec![" The `volume` group.".to_owned()]));
+}
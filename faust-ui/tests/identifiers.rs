@@ -0,0 +1,247 @@
+use faust_json::FaustJson;
+use faust_ui::{generate_ui_code_with_options, ParamAccess, UiOptions};
+use quote::ToTokens;
+use syn::{Fields, File, ImplItem, Item, Type};
+
+fn generate(ui: &str) -> File {
+    generate_with_options(ui, UiOptions::default())
+}
+
+fn generate_with_options(ui: &str, options: UiOptions) -> File {
+    let json = format!(
+        r#"{{"name":"awkward","filename":"awkward.dsp","version":"2.0","compile_options":"",
+            "size":0,"inputs":0,"outputs":1,"ui":[{ui}]}}"#
+    );
+    let faust_json: FaustJson = serde_json::from_str(&json).expect("valid json");
+    syn::parse2(generate_ui_code_with_options(
+        &faust_json,
+        "Awkward",
+        options,
+    ))
+    .expect("valid rust")
+}
+
+fn slider(label: &str, shortname: &str, varname: &str) -> String {
+    format!(
+        r#"{{"type":"hslider","label":"{label}","shortname":"{shortname}",
+            "address":"/awkward/{label}","varname":"{varname}",
+            "init":0,"min":0,"max":1,"step":0.01}}"#
+    )
+}
+
+fn variants(file: &File, name: &str) -> Vec<String> {
+    file.items
+        .iter()
+        .find_map(|item| match item {
+            Item::Enum(item) if item.ident == name => Some(
+                item.variants
+                    .iter()
+                    .map(|variant| variant.ident.to_string())
+                    .collect(),
+            ),
+            _ => None,
+        })
+        .unwrap_or_else(|| panic!("no enum {name}"))
+}
+
+fn fields(file: &File, name: &str) -> Vec<String> {
+    file.items
+        .iter()
+        .find_map(|item| match item {
+            Item::Struct(item) if item.ident == name => match &item.fields {
+                Fields::Named(fields) => Some(
+                    fields
+                        .named
+                        .iter()
+                        .map(|field| field.ident.as_ref().expect("named").to_string())
+                        .collect(),
+                ),
+                _ => None,
+            },
+            _ => None,
+        })
+        .unwrap_or_else(|| panic!("no struct {name}"))
+}
+
+/// The methods of all `impl Awkward` blocks.
+fn dsp_methods(file: &File) -> Vec<String> {
+    file.items
+        .iter()
+        .filter_map(|item| match item {
+            Item::Impl(item) if item.trait_.is_none() => match &*item.self_ty {
+                Type::Path(path) if path.path.is_ident("Awkward") => Some(&item.items),
+                _ => None,
+            },
+            _ => None,
+        })
+        .flatten()
+        .filter_map(|item| match item {
+            ImplItem::Fn(method) => Some(method.sig.ident.to_string()),
+            _ => None,
+        })
+        .collect()
+}
+
+#[test]
+fn sanitizes_labels() {
+    let file = generate(&format!(
+        r#"{{"type":"vgroup","label":"My Synth 2.0","items":[{},{},{},{},{}]}}"#,
+        slider("Gain (dB)", "Gain (dB)", "fHslider0"),
+        slider("1st harmonic", "1st harmonic", "fHslider1"),
+        slider("type", "type", "fHslider2"),
+        slider("Lautstärke", "Lautstärke", "fHslider3"),
+        slider("***", "***", "fHslider4"),
+    ));
+    assert_eq!(
+        variants(&file, "UIActiveValue"),
+        ["GainDB", "V1stHarmonic", "Type", "LautstRke", "Unnamed"]
+    );
+    assert_eq!(
+        fields(&file, "DspUiMySynth20"),
+        [
+            "gain_d_b",
+            "_1st_harmonic",
+            "type_",
+            "lautst_rke",
+            "unnamed"
+        ]
+    );
+    assert_eq!(
+        fields(&file, "Params"),
+        [
+            "gain_d_b",
+            "_1st_harmonic",
+            "type_",
+            "lautst_rke",
+            "unnamed"
+        ]
+    );
+}
+
+#[test]
+fn numbers_duplicates() {
+    let group = |label: &str, first: &str, second: &str, n: usize| {
+        format!(
+            r#"{{"type":"hgroup","label":"{label}","items":[{},{}]}}"#,
+            slider("freq", first, &format!("fHslider{n}")),
+            slider("Freq", second, &format!("fHslider{}", n + 1)),
+        )
+    };
+    let file = generate(&format!(
+        r#"{{"type":"tgroup","label":"self","items":[{},{}]}}"#,
+        group("osc", "freq", "Freq", 0),
+        group("osc", "freq_", "freq ", 2),
+    ));
+    assert_eq!(
+        variants(&file, "UIActiveValue"),
+        ["Freq", "Freq2", "Freq3", "Freq4"]
+    );
+    assert_eq!(fields(&file, "DspUiSelf"), ["osc", "osc_2"]);
+    assert_eq!(fields(&file, "DspUiSelfOsc"), ["freq", "freq_2"]);
+    assert_eq!(fields(&file, "DspUiSelfOsc2"), ["freq", "freq_2"]);
+}
+
+#[test]
+fn skips_soundfiles() {
+    let file = generate(&format!(
+        r#"{{"type":"vgroup","label":"awkward","items":[
+            {{"type":"soundfile","label":"sample","url":"{{'a.wav'}}",
+              "address":"/awkward/sample","varname":"fSoundfile0"}},
+            {}]}}"#,
+        slider("gain", "gain", "fHslider0"),
+    ));
+    assert_eq!(variants(&file, "UIActiveValue"), ["Gain"]);
+    assert_eq!(fields(&file, "DspUiAwkward"), ["gain"]);
+}
+
+/// The body of the `param_index()` method of the enum `name`.
+fn param_index_body(file: &File, name: &str) -> String {
+    file.items
+        .iter()
+        .filter_map(|item| match item {
+            Item::Impl(item) if item.trait_.is_none() => match &*item.self_ty {
+                Type::Path(path) if path.path.is_ident(name) => Some(&item.items),
+                _ => None,
+            },
+            _ => None,
+        })
+        .flatten()
+        .find_map(|item| match item {
+            ImplItem::Fn(method) if method.sig.ident == "param_index" => {
+                Some(method.block.to_token_stream().to_string())
+            }
+            _ => None,
+        })
+        .unwrap_or_else(|| panic!("no param_index() on {name}"))
+}
+
+#[test]
+fn counts_soundfiles_in_param_indices() {
+    let file = generate_with_options(
+        &format!(
+            r#"{{"type":"vgroup","label":"awkward","items":[
+                {{"type":"soundfile","label":"sample","url":"{{'a.wav'}}",
+                  "address":"/awkward/sample","varname":"fSoundfile0"}},
+                {},
+                {{"type":"hbargraph","label":"level","shortname":"level",
+                  "address":"/awkward/level","varname":"fHbargraph0","min":0,"max":1}}]}}"#,
+            slider("gain", "gain", "fHslider0"),
+        ),
+        UiOptions {
+            access: ParamAccess::Index,
+            ..UiOptions::default()
+        },
+    );
+    // The C backend wrapper reserves index 0 for the soundfile.
+    assert!(param_index_body(&file, "UIActive").contains("ParamIndex (1i32)"));
+    assert!(param_index_body(&file, "UIPassive").contains("ParamIndex (2i32)"));
+}
+
+#[test]
+fn keeps_reserved_names_free() {
+    let file = generate(
+        r#"{"type":"vgroup","label":"awkward","items":[
+            {"type":"nentry","label":"display","shortname":"display",
+              "address":"/awkward/display","varname":"fEntry0",
+              "meta":[{"style":"menu{'1':0;'one':1;'One':2;'Self':3}"}],
+              "init":0,"min":0,"max":3,"step":1}]}"#,
+    );
+    assert_eq!(
+        variants(&file, "DisplayChoice"),
+        ["V1", "One", "One2", "VSelf"]
+    );
+}
+
+#[test]
+fn names_accessors() {
+    let file = generate_with_options(
+        &format!(
+            r#"{{"type":"vgroup","label":"awkward","items":[{},{},{},{},
+                {{"type":"hbargraph","label":"level","shortname":"level",
+                  "address":"/awkward/level","varname":"fHbargraph0","min":0,"max":1}}]}}"#,
+            slider("gain", "gain", "fHslider0"),
+            slider("set gain", "set gain", "fHslider1"),
+            slider("compute", "compute", "fHslider2"),
+            slider("param", "param", "fHslider3"),
+        ),
+        UiOptions {
+            accessors: true,
+            ..UiOptions::default()
+        },
+    );
+    assert_eq!(
+        dsp_methods(&file),
+        [
+            "set",
+            "gain",
+            "set_gain",
+            "set_gain_2",
+            "set_set_gain_2",
+            "compute_2",
+            "set_compute_2",
+            "param_2",
+            "set_param_2",
+            "level",
+        ]
+    );
+}